@@ -1 +1,15 @@
+mod catastrophe;
+mod connectivity;
+mod draw;
+pub mod game;
+mod game_over;
+pub mod log;
+pub mod move_generator;
 mod operations;
+pub mod perft;
+mod resolver;
+pub mod setup;
+mod validation;
+pub mod zobrist;
+
+pub use game::GameState;
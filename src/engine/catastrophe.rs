@@ -0,0 +1,515 @@
+use super::operations::{
+    BasicOperation,
+    bank::UpdateBank,
+    common::UpdateOneDelta,
+    fleet::UpdateFleet,
+    stars::{DestroyStar, DestroyStarSelector},
+    systems::ForgetSystem,
+};
+use crate::public::{board::*, common::*, rules::RuleSet};
+use strum::IntoEnumIterator;
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct Overpopulation {
+    pub star_system: SystemId,
+    pub color: Color,
+}
+
+pub fn population(star_system: &StarSystem, color: Color) -> u8 {
+    let stars = star_system
+        .center
+        .stars()
+        .filter(|Star(pyramid)| pyramid.color == color)
+        .count() as u8;
+    let starships: u8 = star_system
+        .fleets
+        .iter()
+        .flat_map(|fleet| fleet.starships.iter())
+        .filter(|(Starship(pyramid), _)| pyramid.color == color)
+        .map(|(_, count)| count.get())
+        .sum();
+    stars + starships
+}
+
+pub fn is_overpopulated(star_system: &StarSystem, color: Color, rules: &RuleSet) -> bool {
+    population(star_system, color) >= rules.overpopulation_threshold.get()
+}
+
+pub fn overpopulations(game_board: &GameBoard, rules: &RuleSet) -> Vec<Overpopulation> {
+    game_board
+        .star_systems()
+        .flat_map(|star_system| {
+            Color::iter()
+                .filter(|color| is_overpopulated(star_system, *color, rules))
+                .map(|color| Overpopulation {
+                    star_system: star_system.id,
+                    color,
+                })
+        })
+        .collect()
+}
+
+pub fn resolve_catastrophe(
+    star_system: &StarSystem,
+    color: Color,
+    eliminated: &[Player],
+) -> Vec<BasicOperation> {
+    let mut operations = vec![];
+    let mut remaining_starships = false;
+    for (player, fleet) in star_system.players() {
+        for (starship, count) in &fleet.starships {
+            if starship.0.color == color {
+                for _ in 0..count.get() {
+                    return_starship(&mut operations, star_system.id, player, *starship);
+                }
+            } else {
+                remaining_starships = true;
+            }
+        }
+    }
+
+    let stars: Vec<Star> = star_system.center.stars().collect();
+    let remaining_stars = return_stars(&mut operations, star_system.id, &stars, |star| {
+        star.0.color == color
+    });
+
+    // Homeworlds are never forgotten, but a homeworld without stars can't keep its ships
+    if remaining_stars.is_empty() {
+        for (player, fleet) in star_system.players() {
+            for (starship, count) in &fleet.starships {
+                if starship.0.color == color {
+                    continue;
+                }
+                for _ in 0..count.get() {
+                    return_starship(&mut operations, star_system.id, player, *starship);
+                }
+            }
+        }
+        if star_system.is_homeworld_for.is_none() {
+            forget_system(&mut operations, star_system.id);
+        }
+    } else if !remaining_starships {
+        abandon_stars(&mut operations, star_system, &remaining_stars, eliminated);
+    }
+    operations
+}
+
+// Takes the starships of eliminated players off the board. Systems they leave without
+// starships are abandoned, which includes their own homeworlds unless an opponent is there.
+// The eliminated players have to include the removed ones.
+pub fn remove_players(
+    game_board: &GameBoard,
+    eliminated: &[Player],
+    removed: &[Player],
+) -> Vec<BasicOperation> {
+    let mut operations = vec![];
+    for star_system in game_board.star_systems() {
+        let mut remaining_starships = false;
+        let mut removed_starships = false;
+        for (player, fleet) in star_system.players() {
+            if !removed.contains(&player) {
+                remaining_starships |= !fleet.is_empty();
+                continue;
+            }
+            for (starship, count) in &fleet.starships {
+                removed_starships = true;
+                for _ in 0..count.get() {
+                    return_starship(&mut operations, star_system.id, player, *starship);
+                }
+            }
+        }
+        let lost_homeworld = star_system
+            .is_homeworld_for
+            .is_some_and(|owner| removed.contains(&owner));
+        if !remaining_starships && (removed_starships || lost_homeworld) {
+            abandon_system(&mut operations, star_system, eliminated);
+        }
+    }
+    operations
+}
+
+// To be called once the last starship leaves the system. Homeworlds keep their slot, so
+// only a homeworld of an eliminated player gives its stars back to the bank.
+pub fn abandon_system(
+    operations: &mut Vec<BasicOperation>,
+    star_system: &StarSystem,
+    eliminated: &[Player],
+) {
+    let stars: Vec<Star> = star_system.center.stars().collect();
+    abandon_stars(operations, star_system, &stars, eliminated);
+}
+
+fn abandon_stars(
+    operations: &mut Vec<BasicOperation>,
+    star_system: &StarSystem,
+    stars: &[Star],
+    eliminated: &[Player],
+) {
+    match star_system.is_homeworld_for {
+        // Forgetting the system returns its remaining stars to the bank
+        None => forget_system(operations, star_system.id),
+        Some(owner) if eliminated.contains(&owner) => {
+            return_stars(operations, star_system.id, stars, |_| true);
+        }
+        Some(_) => {}
+    }
+}
+
+// Returns the selected stars to the bank and gives back the stars which are left in the system
+fn return_stars(
+    operations: &mut Vec<BasicOperation>,
+    star_system: SystemId,
+    stars: &[Star],
+    should_return: impl Fn(&Star) -> bool,
+) -> Vec<Star> {
+    match *stars {
+        [star] if should_return(&star) => {
+            return_star(operations, star_system, star, DestroyStarSelector::Single);
+            vec![]
+        }
+        [alpha, beta] => match (should_return(&alpha), should_return(&beta)) {
+            (true, true) => {
+                return_star(
+                    operations,
+                    star_system,
+                    alpha,
+                    DestroyStarSelector::Binary(BinaryStarId::Alpha),
+                );
+                return_star(operations, star_system, beta, DestroyStarSelector::Single);
+                vec![]
+            }
+            (true, false) => {
+                return_star(
+                    operations,
+                    star_system,
+                    alpha,
+                    DestroyStarSelector::Binary(BinaryStarId::Alpha),
+                );
+                vec![beta]
+            }
+            (false, true) => {
+                return_star(
+                    operations,
+                    star_system,
+                    beta,
+                    DestroyStarSelector::Binary(BinaryStarId::Beta),
+                );
+                vec![alpha]
+            }
+            (false, false) => stars.to_vec(),
+        },
+        _ => stars.to_vec(),
+    }
+}
+
+fn return_starship(
+    operations: &mut Vec<BasicOperation>,
+    star_system: SystemId,
+    player: Player,
+    starship: Starship,
+) {
+    operations.push(
+        UpdateFleet {
+            star_system,
+            player,
+            starship,
+            delta: UpdateOneDelta::RemoveOne,
+        }
+        .into(),
+    );
+    operations.push(
+        UpdateBank {
+            pyramid: starship.0,
+            delta: UpdateOneDelta::AddOne,
+        }
+        .into(),
+    );
+}
+
+fn return_star(
+    operations: &mut Vec<BasicOperation>,
+    star_system: SystemId,
+    star: Star,
+    selector: DestroyStarSelector,
+) {
+    operations.push(
+        DestroyStar {
+            star_system,
+            star: selector,
+        }
+        .into(),
+    );
+    operations.push(
+        UpdateBank {
+            pyramid: star.0,
+            delta: UpdateOneDelta::AddOne,
+        }
+        .into(),
+    );
+}
+
+fn forget_system(operations: &mut Vec<BasicOperation>, star_system: SystemId) {
+    operations.push(ForgetSystem { star_system }.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::Apply;
+    use crate::engine::setup::new_game;
+    use crate::public::current_turn::*;
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn fleet(starships: &[Pyramid]) -> Fleet {
+        let mut fleet = Fleet::default();
+        for pyramid in starships {
+            let count = fleet
+                .starships
+                .get(&Starship(*pyramid))
+                .map_or(1, |count| count.get() + 1);
+            fleet
+                .starships
+                .insert(Starship(*pyramid), NonZero::new(count).unwrap());
+        }
+        fleet
+    }
+
+    fn create_test_state(
+        center: StarSystemCenter,
+        first: &[Pyramid],
+        second: &[Pyramid],
+    ) -> CurrentTurnState {
+        let mut state = new_game(
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        );
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+        state.game_board.bank.pyramids.clear();
+        state.game_board.discovered_systems.push(StarSystem {
+            id: SystemId(2),
+            name: Some("Alpha".to_string()),
+            center,
+            fleets: vec![fleet(first), fleet(second)],
+            is_homeworld_for: None,
+        });
+        state
+    }
+
+    fn apply_catastrophe(state: &mut CurrentTurnState, color: Color) {
+        let operations = resolve_catastrophe(&state.game_board.discovered_systems[0], color, &[]);
+        for operation in operations {
+            operation.apply(state).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_overpopulations() {
+        let state = create_test_state(
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Red, Size::Small))),
+            &[
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Red, Size::Medium),
+            ],
+            &[
+                pyramid(Color::Red, Size::Large),
+                pyramid(Color::Blue, Size::Large),
+            ],
+        );
+
+        assert_eq!(
+            overpopulations(&state.game_board, &RuleSet::default()),
+            vec![Overpopulation {
+                star_system: SystemId(2),
+                color: Color::Red,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_not_overpopulated() {
+        let state = create_test_state(
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Red, Size::Small))),
+            &[
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Blue, Size::Medium),
+            ],
+            &[pyramid(Color::Red, Size::Large)],
+        );
+
+        assert_eq!(
+            population(&state.game_board.discovered_systems[0], Color::Red),
+            3
+        );
+        assert!(overpopulations(&state.game_board, &RuleSet::default()).is_empty());
+    }
+
+    #[test]
+    fn test_catastrophe_removes_color() {
+        let mut state = create_test_state(
+            StarSystemCenter::BinaryStar {
+                alpha: Star(pyramid(Color::Green, Size::Small)),
+                beta: Star(pyramid(Color::Blue, Size::Large)),
+            },
+            &[
+                pyramid(Color::Green, Size::Medium),
+                pyramid(Color::Green, Size::Medium),
+                pyramid(Color::Yellow, Size::Small),
+            ],
+            &[
+                pyramid(Color::Green, Size::Large),
+                pyramid(Color::Red, Size::Small),
+            ],
+        );
+
+        apply_catastrophe(&mut state, Color::Green);
+
+        let system = &state.game_board.discovered_systems[0];
+        assert!(matches!(
+            system.center,
+            StarSystemCenter::SingleStar(Star(it)) if it == pyramid(Color::Blue, Size::Large)
+        ));
+        assert!(system.fleets[0].contains(Starship(pyramid(Color::Yellow, Size::Small))));
+        assert!(!system.fleets[0].contains(Starship(pyramid(Color::Green, Size::Medium))));
+        assert!(system.fleets[1].contains(Starship(pyramid(Color::Red, Size::Small))));
+        assert!(!system.fleets[1].contains(Starship(pyramid(Color::Green, Size::Large))));
+
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Green, Size::Small)), 1);
+        assert_eq!(bank.count(pyramid(Color::Green, Size::Medium)), 2);
+        assert_eq!(bank.count(pyramid(Color::Green, Size::Large)), 1);
+    }
+
+    #[test]
+    fn test_catastrophe_destroying_star_cleans_up_system() {
+        let mut state = create_test_state(
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Red, Size::Small))),
+            &[
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Blue, Size::Medium),
+            ],
+            &[pyramid(Color::Red, Size::Large)],
+        );
+
+        apply_catastrophe(&mut state, Color::Red);
+
+        assert!(state.game_board.discovered_systems.is_empty());
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Small)), 1);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Medium)), 2);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Large)), 1);
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Medium)), 1);
+    }
+
+    #[test]
+    fn test_catastrophe_destroying_ships_cleans_up_system() {
+        let mut state = create_test_state(
+            StarSystemCenter::BinaryStar {
+                alpha: Star(pyramid(Color::Yellow, Size::Small)),
+                beta: Star(pyramid(Color::Yellow, Size::Large)),
+            },
+            &[
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Red, Size::Small),
+            ],
+            &[
+                pyramid(Color::Red, Size::Large),
+                pyramid(Color::Red, Size::Large),
+            ],
+        );
+
+        apply_catastrophe(&mut state, Color::Red);
+
+        assert!(state.game_board.discovered_systems.is_empty());
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Yellow, Size::Small)), 1);
+        assert_eq!(bank.count(pyramid(Color::Yellow, Size::Large)), 1);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Large)), 2);
+    }
+
+    #[test]
+    fn test_catastrophe_destroying_both_binary_stars() {
+        let mut state = create_test_state(
+            StarSystemCenter::BinaryStar {
+                alpha: Star(pyramid(Color::Blue, Size::Small)),
+                beta: Star(pyramid(Color::Blue, Size::Large)),
+            },
+            &[
+                pyramid(Color::Blue, Size::Medium),
+                pyramid(Color::Red, Size::Small),
+            ],
+            &[pyramid(Color::Blue, Size::Medium)],
+        );
+
+        apply_catastrophe(&mut state, Color::Blue);
+
+        assert!(state.game_board.discovered_systems.is_empty());
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Small)), 1);
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Medium)), 2);
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Large)), 1);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Small)), 1);
+    }
+
+    #[test]
+    fn test_overpopulation_threshold() {
+        let state = create_test_state(
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Red, Size::Small))),
+            &[
+                pyramid(Color::Red, Size::Medium),
+                pyramid(Color::Blue, Size::Medium),
+            ],
+            &[pyramid(Color::Red, Size::Large)],
+        );
+        let rules = RuleSet {
+            overpopulation_threshold: NonZero::new(3).unwrap(),
+            ..RuleSet::default()
+        };
+
+        assert_eq!(
+            overpopulations(&state.game_board, &rules),
+            vec![Overpopulation {
+                star_system: SystemId(2),
+                color: Color::Red,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_catastrophe_destroying_homeworld_stars() {
+        let mut state = create_test_state(
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Blue, Size::Large))),
+            &[pyramid(Color::Blue, Size::Small)],
+            &[],
+        );
+        let homeworld = state.game_board.homeworld_mut(Player::FIRST);
+        homeworld.center = StarSystemCenter::BinaryStar {
+            alpha: Star(pyramid(Color::Red, Size::Small)),
+            beta: Star(pyramid(Color::Red, Size::Medium)),
+        };
+        homeworld.fleets = vec![
+            fleet(&[pyramid(Color::Green, Size::Large)]),
+            fleet(&[pyramid(Color::Yellow, Size::Small)]),
+        ];
+
+        let operations =
+            resolve_catastrophe(state.game_board.homeworld(Player::FIRST), Color::Red, &[]);
+        for operation in operations {
+            operation.apply(&mut state).unwrap();
+        }
+
+        // Every ship goes back to the bank, but the homeworld stays on the board
+        let homeworld = state.game_board.homeworld(Player::FIRST);
+        assert_eq!(homeworld.center, StarSystemCenter::Empty);
+        assert!(homeworld.is_empty());
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Small)), 1);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Medium)), 1);
+        assert_eq!(bank.count(pyramid(Color::Green, Size::Large)), 1);
+        assert_eq!(bank.count(pyramid(Color::Yellow, Size::Small)), 1);
+    }
+}
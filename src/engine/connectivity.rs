@@ -0,0 +1,104 @@
+use crate::public::board::*;
+
+pub fn are_connected(first: &StarSystemCenter, second: &StarSystemCenter) -> bool {
+    // Two systems are connected when they share no star size. A system without stars, like
+    // a homeworld whose stars were all destroyed, would share no size with any system, so it
+    // is treated as connected to none instead
+    if first.stars().next().is_none() || second.stars().next().is_none() {
+        return false;
+    }
+    first.stars().all(|Star(first_pyramid)| {
+        second
+            .stars()
+            .all(|Star(second_pyramid)| first_pyramid.size != second_pyramid.size)
+    })
+}
+
+pub fn can_discover(origin: &StarSystemCenter, star: Star) -> bool {
+    are_connected(origin, &StarSystemCenter::SingleStar(star))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::common::*;
+
+    fn star(color: Color, size: Size) -> Star {
+        Star(Pyramid { color, size })
+    }
+
+    #[test]
+    fn test_single_stars_of_different_sizes_are_connected() {
+        let first = StarSystemCenter::SingleStar(star(Color::Red, Size::Small));
+        let second = StarSystemCenter::SingleStar(star(Color::Red, Size::Medium));
+
+        assert!(are_connected(&first, &second));
+        assert!(are_connected(&second, &first));
+    }
+
+    #[test]
+    fn test_single_stars_of_same_size_are_not_connected() {
+        let first = StarSystemCenter::SingleStar(star(Color::Red, Size::Large));
+        let second = StarSystemCenter::SingleStar(star(Color::Blue, Size::Large));
+
+        assert!(!are_connected(&first, &second));
+    }
+
+    #[test]
+    fn test_binary_star_connectivity() {
+        let homeworld = StarSystemCenter::BinaryStar {
+            alpha: star(Color::Blue, Size::Small),
+            beta: star(Color::Yellow, Size::Large),
+        };
+
+        assert!(are_connected(
+            &homeworld,
+            &StarSystemCenter::SingleStar(star(Color::Green, Size::Medium))
+        ));
+        assert!(!are_connected(
+            &homeworld,
+            &StarSystemCenter::SingleStar(star(Color::Green, Size::Small))
+        ));
+        assert!(!are_connected(
+            &homeworld,
+            &StarSystemCenter::SingleStar(star(Color::Green, Size::Large))
+        ));
+    }
+
+    #[test]
+    fn test_systems_without_stars_are_not_connected() {
+        let single = StarSystemCenter::SingleStar(star(Color::Red, Size::Small));
+
+        assert!(!are_connected(&StarSystemCenter::Empty, &single));
+        assert!(!are_connected(&single, &StarSystemCenter::Empty));
+        assert!(!can_discover(
+            &StarSystemCenter::Empty,
+            star(Color::Red, Size::Large)
+        ));
+    }
+
+    #[test]
+    fn test_binary_stars_sharing_one_size_are_not_connected() {
+        let first = StarSystemCenter::BinaryStar {
+            alpha: star(Color::Blue, Size::Small),
+            beta: star(Color::Yellow, Size::Large),
+        };
+        let second = StarSystemCenter::BinaryStar {
+            alpha: star(Color::Red, Size::Medium),
+            beta: star(Color::Green, Size::Large),
+        };
+
+        assert!(!are_connected(&first, &second));
+    }
+
+    #[test]
+    fn test_can_discover() {
+        let origin = StarSystemCenter::BinaryStar {
+            alpha: star(Color::Blue, Size::Small),
+            beta: star(Color::Yellow, Size::Medium),
+        };
+
+        assert!(can_discover(&origin, star(Color::Green, Size::Large)));
+        assert!(!can_discover(&origin, star(Color::Green, Size::Medium)));
+    }
+}
@@ -0,0 +1,47 @@
+use super::GameState;
+use crate::public::{actions::Action, game_result::DrawReason};
+
+// Counts the position the current turn starts from, returns how often it occurred
+pub fn record_turn_start(game: &mut GameState) -> u32 {
+    let count = game
+        .position_counts
+        .entry(game.current_turn.hash)
+        .or_default();
+    *count += 1;
+    *count
+}
+
+// Applies the draw rules once a turn was ended and the next one is about to start
+pub fn check_draw(game: &mut GameState, ended_turn_actions: &[Action]) -> Option<DrawReason> {
+    let rules = game.draw_rules;
+    let occurrences = record_turn_start(game);
+    if ended_turn_actions
+        .iter()
+        .all(|action| *action == Action::Pass)
+    {
+        game.consecutive_passes += 1;
+    } else {
+        game.consecutive_passes = 0;
+    }
+    game.claimable_draw = None;
+
+    // Every player still in the game has passed once in a row
+    let player_count = game.current_turn.active_players().count() as u32;
+    if rules.mutual_pass && game.consecutive_passes >= player_count {
+        return Some(DrawReason::MutualPass);
+    }
+    if let Some(limit) = rules.turn_limit
+        && game.turn_number >= limit.get()
+    {
+        return Some(DrawReason::TurnLimit);
+    }
+    if let Some(repetition) = rules.repetition
+        && occurrences >= repetition.count.get()
+    {
+        if repetition.automatic {
+            return Some(DrawReason::Repetition);
+        }
+        game.claimable_draw = Some(DrawReason::Repetition);
+    }
+    None
+}
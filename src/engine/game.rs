@@ -0,0 +1,447 @@
+use super::draw::{check_draw, record_turn_start};
+use super::game_over::{check_game_over, end_of_turn_operations};
+use super::log::GameLog;
+use super::operations::{
+    OperationError,
+    transaction::{TransactionError, apply_all},
+};
+use super::resolver::{ResolveActionError, resolve_action};
+use super::setup::{HomeworldChoice, SetupError, new_game_with_rules, set_up_homeworld};
+use crate::public::{
+    actions::Action,
+    board::GameBoardError,
+    common::Player,
+    current_turn::*,
+    draw_rules::DrawRules,
+    game_result::{DrawReason, GameResult},
+    rules::RuleSet,
+    turn_state::*,
+};
+use std::collections::HashMap;
+use std::mem::take;
+use thiserror::Error;
+
+pub struct GameState {
+    pub current_turn: CurrentTurnState,
+    pub current_turn_actions: Vec<Action>,
+    pub turn_number: u32,
+    pub history: Vec<CompletedTurn>,
+    pub result: Option<GameResult>,
+    pub log: GameLog,
+    pub draw_rules: DrawRules,
+    // How often each position occurred at the start of a turn, by its hash
+    pub position_counts: HashMap<u64, u32>,
+    // Number of turns in a row ended without any action but passing
+    pub consecutive_passes: u32,
+    // A draw which either player may claim during the current turn
+    pub claimable_draw: Option<DrawReason>,
+}
+
+impl GameState {
+    pub fn new(
+        homeworld_first_name: Option<String>,
+        homeworld_second_name: Option<String>,
+    ) -> Self {
+        GameState::new_with_rules(
+            vec![homeworld_first_name, homeworld_second_name],
+            RuleSet::default(),
+        )
+        .expect("two players are enough for a game")
+    }
+
+    // One homeworld name for each player, in turn order
+    pub fn new_with_rules(
+        homeworld_names: Vec<Option<String>>,
+        rules: RuleSet,
+    ) -> Result<Self, GameBoardError> {
+        let current_turn = new_game_with_rules(homeworld_names, rules)?;
+        Ok(GameState {
+            log: GameLog::new(current_turn.clone()),
+            current_turn,
+            current_turn_actions: vec![],
+            turn_number: 1,
+            history: vec![],
+            result: None,
+            draw_rules: DrawRules::default(),
+            position_counts: HashMap::new(),
+            consecutive_passes: 0,
+            claimable_draw: None,
+        })
+    }
+
+    pub fn player(&self) -> Player {
+        self.current_turn.player
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GameError {
+    #[error("the game is already over")]
+    GameIsOver,
+    #[error("there is no draw to claim")]
+    NoDrawToClaim,
+    #[error("cannot resolve action")]
+    ResolveActionError(#[from] ResolveActionError),
+    #[error("cannot apply operation")]
+    OperationError(#[from] OperationError),
+    #[error("cannot apply action")]
+    TransactionError(#[from] TransactionError),
+    #[error("cannot set up homeworld")]
+    SetupError(#[from] SetupError),
+}
+
+pub fn set_up(game: &mut GameState, choice: HomeworldChoice) -> Result<(), GameError> {
+    let operations = set_up_homeworld(&mut game.current_turn, choice)?;
+    game.log.record_action(operations);
+    game.log.record_end_of_turn(vec![]);
+    if game.current_turn.current_turn_status == CurrentTurnStatus::MakingActions {
+        record_turn_start(game);
+    }
+    Ok(())
+}
+
+pub fn play_action(game: &mut GameState, action: Action) -> Result<(), GameError> {
+    if game.result.is_some() {
+        return Err(GameError::GameIsOver);
+    }
+    let operations = resolve_action(&game.current_turn, &action)?;
+    apply_all(&mut game.current_turn, operations.clone())?;
+    game.log.record_action(operations);
+    game.current_turn_actions.push(action);
+    Ok(())
+}
+
+pub fn end_turn(game: &mut GameState) -> Result<Option<GameResult>, GameError> {
+    if game.result.is_some() {
+        return Err(GameError::GameIsOver);
+    }
+    let player = game.current_turn.player;
+    let ended_with = game.current_turn.current_turn_status.clone();
+    let result = check_game_over(&game.current_turn);
+    if result.is_none() {
+        let operations = end_of_turn_operations(&game.current_turn);
+        apply_all(&mut game.current_turn, operations.clone()).map_err(|it| it.error)?;
+        game.log.record_end_of_turn(operations);
+    }
+    let actions = take(&mut game.current_turn_actions);
+    let result =
+        result.or_else(|| check_draw(game, &actions).map(|reason| GameResult::Draw { reason }));
+    game.history.push(CompletedTurn {
+        player,
+        actions,
+        ended_with,
+    });
+    match result {
+        Some(result) => game.result = Some(result),
+        None => game.turn_number += 1,
+    }
+    Ok(result)
+}
+
+pub fn claim_draw(game: &mut GameState) -> Result<GameResult, GameError> {
+    if game.result.is_some() {
+        return Err(GameError::GameIsOver);
+    }
+    let reason = game.claimable_draw.ok_or(GameError::NoDrawToClaim)?;
+    let result = GameResult::Draw { reason };
+    game.result = Some(result);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::{
+        actions::*, board::*, common::*, draw_rules::*, game_result::*, rules::RuleSet,
+    };
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_game() -> GameState {
+        let mut game = GameState::new(
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        );
+        for (alpha, beta, starship) in [
+            (
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+                pyramid(Color::Green, Size::Large),
+            ),
+            (
+                pyramid(Color::Yellow, Size::Large),
+                pyramid(Color::Blue, Size::Small),
+                pyramid(Color::Green, Size::Large),
+            ),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(alpha),
+                beta: Star(beta),
+                starship: Starship(starship),
+            };
+            set_up(&mut game, choice).unwrap();
+        }
+        game
+    }
+
+    fn trade(color: Color, new_color: Color) -> Action {
+        Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Trade {
+                starship: Starship(pyramid(color, Size::Large)),
+                new_color,
+            }),
+        }
+    }
+
+    // The first player trades back and forth while the second one passes
+    fn play_trading_turns(game: &mut GameState, turns: usize) -> Option<GameResult> {
+        let mut result = None;
+        for _ in 0..turns {
+            let action = match (game.turn_number - 1) % 4 {
+                0 => trade(Color::Green, Color::Blue),
+                2 => trade(Color::Blue, Color::Green),
+                _ => Action::Pass,
+            };
+            play_action(game, action).unwrap();
+            result = end_turn(game).unwrap();
+        }
+        result
+    }
+
+    #[test]
+    fn test_players_take_turns() {
+        let mut game = create_test_game();
+        assert_eq!(game.player(), Player::FIRST);
+
+        assert!(play_action(&mut game, Action::Pass).is_ok());
+        assert!(matches!(end_turn(&mut game), Ok(None)));
+        assert_eq!(game.player(), Player::SECOND);
+        assert_eq!(game.turn_number, 2);
+        assert!(game.current_turn.current_turn_status == CurrentTurnStatus::MakingActions);
+
+        assert!(end_turn(&mut game).is_ok());
+        assert_eq!(game.player(), Player::FIRST);
+        assert_eq!(game.turn_number, 3);
+
+        assert_eq!(game.history.len(), 2);
+        assert_eq!(game.history[0].player, Player::FIRST);
+        assert!(matches!(game.history[0].actions[..], [Action::Pass]));
+        assert!(game.history[0].ended_with == CurrentTurnStatus::Passing);
+        assert_eq!(game.history[1].player, Player::SECOND);
+        assert!(game.history[1].actions.is_empty());
+    }
+
+    #[test]
+    fn test_build_at_homeworld() {
+        let mut game = create_test_game();
+        let action = Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Build {
+                color: Color::Green,
+            }),
+        };
+
+        assert!(play_action(&mut game, action).is_ok());
+        let fleet = &game.current_turn.game_board.homeworlds[0].fleets[0];
+        assert!(fleet.contains(Starship(pyramid(Color::Green, Size::Small))));
+        assert!(fleet.contains(Starship(pyramid(Color::Green, Size::Large))));
+    }
+
+    #[test]
+    fn test_resignation_ends_game() {
+        let mut game = create_test_game();
+        assert!(end_turn(&mut game).is_ok());
+        assert!(play_action(&mut game, Action::Resign).is_ok());
+
+        let expected = GameResult::Win {
+            winner: Player::FIRST,
+            reason: LossReason::Resigned,
+        };
+        assert_eq!(end_turn(&mut game).unwrap(), Some(expected));
+        assert_eq!(game.result, Some(expected));
+        assert_eq!(game.history.len(), 2);
+        assert!(matches!(
+            play_action(&mut game, Action::Pass),
+            Err(GameError::GameIsOver)
+        ));
+        assert!(matches!(end_turn(&mut game), Err(GameError::GameIsOver)));
+    }
+
+    #[test]
+    fn test_homeworld_loss_is_checked_after_turn() {
+        let mut game = create_test_game();
+        game.current_turn.game_board.homeworlds[1].fleets[1] = Fleet::default();
+
+        assert_eq!(
+            end_turn(&mut game).unwrap(),
+            Some(GameResult::Win {
+                winner: Player::FIRST,
+                reason: LossReason::HomeworldStarshipsLost,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cannot_end_turn_during_setup() {
+        let mut game = GameState::new(
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        );
+
+        let result = end_turn(&mut game);
+        assert!(matches!(result, Err(GameError::OperationError(_))));
+        assert!(game.history.is_empty());
+    }
+
+    #[test]
+    fn test_log_replays_to_current_state() {
+        let mut game = create_test_game();
+        let build = Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Build {
+                color: Color::Green,
+            }),
+        };
+        assert!(play_action(&mut game, build).is_ok());
+        assert!(end_turn(&mut game).is_ok());
+        assert!(play_action(&mut game, Action::Pass).is_ok());
+
+        // Two setup turns, the first turn and the one in progress
+        assert_eq!(game.log.turns.len(), 4);
+        assert_eq!(game.log.replay().unwrap(), game.current_turn);
+    }
+
+    #[test]
+    fn test_three_player_game() {
+        let mut game =
+            GameState::new_with_rules(vec![None, None, None], RuleSet::default()).unwrap();
+        for color in [Color::Red, Color::Yellow, Color::Blue] {
+            let choice = HomeworldChoice {
+                alpha: Star(pyramid(color, Size::Small)),
+                beta: Star(pyramid(color, Size::Medium)),
+                starship: Starship(pyramid(Color::Green, Size::Large)),
+            };
+            set_up(&mut game, choice).unwrap();
+        }
+
+        // The second player drops out, the other two play on without them
+        assert!(matches!(end_turn(&mut game), Ok(None)));
+        play_action(&mut game, Action::Resign).unwrap();
+        assert!(matches!(end_turn(&mut game), Ok(None)));
+        assert_eq!(game.current_turn.eliminated, vec![Player::SECOND]);
+        assert_eq!(game.player(), Player(2));
+        assert!(matches!(end_turn(&mut game), Ok(None)));
+        assert_eq!(game.player(), Player::FIRST);
+        assert!(matches!(end_turn(&mut game), Ok(None)));
+        assert_eq!(game.player(), Player(2));
+        assert_eq!(game.log.replay().unwrap(), game.current_turn);
+
+        play_action(&mut game, Action::Resign).unwrap();
+        assert_eq!(
+            end_turn(&mut game).unwrap(),
+            Some(GameResult::Win {
+                winner: Player::FIRST,
+                reason: LossReason::Resigned,
+            })
+        );
+    }
+
+    #[test]
+    fn test_repetition_draw() {
+        let mut game = create_test_game();
+        game.draw_rules.repetition = Some(RepetitionRule {
+            count: NonZero::new(3).unwrap(),
+            automatic: true,
+        });
+
+        // The position repeats every four turns
+        assert_eq!(play_trading_turns(&mut game, 7), None);
+        let expected = GameResult::Draw {
+            reason: DrawReason::Repetition,
+        };
+        assert_eq!(play_trading_turns(&mut game, 1), Some(expected));
+        assert_eq!(game.result, Some(expected));
+        assert_eq!(game.history.len(), 8);
+    }
+
+    #[test]
+    fn test_claim_repetition_draw() {
+        let mut game = create_test_game();
+        game.draw_rules.repetition = Some(RepetitionRule {
+            count: NonZero::new(2).unwrap(),
+            automatic: false,
+        });
+
+        play_trading_turns(&mut game, 3);
+        assert!(matches!(
+            claim_draw(&mut game),
+            Err(GameError::NoDrawToClaim)
+        ));
+
+        assert_eq!(play_trading_turns(&mut game, 1), None);
+        assert_eq!(game.claimable_draw, Some(DrawReason::Repetition));
+        assert_eq!(
+            claim_draw(&mut game).unwrap(),
+            GameResult::Draw {
+                reason: DrawReason::Repetition
+            }
+        );
+        assert!(matches!(
+            play_action(&mut game, Action::Pass),
+            Err(GameError::GameIsOver)
+        ));
+    }
+
+    #[test]
+    fn test_claimable_draw_expires() {
+        let mut game = create_test_game();
+        game.draw_rules.repetition = Some(RepetitionRule {
+            count: NonZero::new(2).unwrap(),
+            automatic: false,
+        });
+
+        play_trading_turns(&mut game, 4);
+        assert!(game.claimable_draw.is_some());
+
+        // Trading for another color leads to a new position
+        assert!(play_action(&mut game, trade(Color::Green, Color::Red)).is_ok());
+        assert_eq!(end_turn(&mut game).unwrap(), None);
+        assert_eq!(game.claimable_draw, None);
+    }
+
+    #[test]
+    fn test_mutual_pass_draw() {
+        let mut game = create_test_game();
+        game.draw_rules.mutual_pass = true;
+
+        play_trading_turns(&mut game, 1);
+        assert!(play_action(&mut game, Action::Pass).is_ok());
+        assert_eq!(end_turn(&mut game).unwrap(), None);
+        // Ending a turn without any action counts as passing too
+        assert_eq!(
+            end_turn(&mut game).unwrap(),
+            Some(GameResult::Draw {
+                reason: DrawReason::MutualPass
+            })
+        );
+    }
+
+    #[test]
+    fn test_turn_limit() {
+        let mut game = create_test_game();
+        game.draw_rules.turn_limit = Some(NonZero::new(3).unwrap());
+
+        assert_eq!(play_trading_turns(&mut game, 2), None);
+        assert_eq!(
+            play_trading_turns(&mut game, 1),
+            Some(GameResult::Draw {
+                reason: DrawReason::TurnLimit
+            })
+        );
+        assert_eq!(game.turn_number, 3);
+    }
+}
@@ -0,0 +1,324 @@
+use super::catastrophe::remove_players;
+use super::operations::BasicOperation;
+use super::operations::turn::{EliminatePlayer, EndTurn};
+use crate::public::{board::*, common::*, current_turn::*, game_result::*};
+
+pub fn loss_reason(game_board: &GameBoard, player: Player) -> Option<LossReason> {
+    let homeworld = game_board.homeworld(player);
+    if homeworld.center.stars().next().is_none() {
+        Some(LossReason::HomeworldStarsDestroyed)
+    } else if homeworld.fleet(player).is_empty() {
+        Some(LossReason::HomeworldStarshipsLost)
+    } else {
+        None
+    }
+}
+
+// Players still in the game who lose at the end of the current turn, in turn order
+pub fn losers(state: &CurrentTurnState) -> Vec<(Player, LossReason)> {
+    if state.current_turn_status == CurrentTurnStatus::SettingUp {
+        return vec![];
+    }
+    state
+        .active_players()
+        .filter_map(|player| {
+            if player == state.player && state.current_turn_status == CurrentTurnStatus::Resigning {
+                Some((player, LossReason::Resigned))
+            } else {
+                loss_reason(&state.game_board, player).map(|reason| (player, reason))
+            }
+        })
+        .collect()
+}
+
+// The game is over once at most one player is left. Until then, players who lose are
+// eliminated at the end of the turn and the others play on.
+pub fn check_game_over(state: &CurrentTurnState) -> Option<GameResult> {
+    let losers = losers(state);
+    let (_, reason) = *losers.last()?;
+    let mut remaining = state
+        .active_players()
+        .filter(|player| losers.iter().all(|(loser, _)| loser != player));
+    match (remaining.next(), remaining.next()) {
+        (None, _) => Some(GameResult::Draw {
+            reason: DrawReason::AllHomeworldsLost,
+        }),
+        (Some(winner), None) => Some(GameResult::Win { winner, reason }),
+        (Some(_), Some(_)) => None,
+    }
+}
+
+// Eliminates the players who lost and passes the turn on, when the game goes on. The
+// pieces of eliminated players go back to the bank, so none of them are left stranded.
+pub fn end_of_turn_operations(state: &CurrentTurnState) -> Vec<BasicOperation> {
+    let losers: Vec<Player> = losers(state)
+        .into_iter()
+        .map(|(player, _)| player)
+        .collect();
+    let eliminated: Vec<Player> = state.eliminated.iter().chain(&losers).copied().collect();
+    let mut operations = remove_players(&state.game_board, &eliminated, &losers);
+    operations.extend(
+        losers
+            .into_iter()
+            .map(|player| BasicOperation::from(EliminatePlayer { player })),
+    );
+    operations.push(EndTurn.into());
+    operations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::transaction::apply_all;
+    use crate::engine::setup::{new_game, new_game_with_rules};
+    use crate::public::rules::RuleSet;
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        create_state(vec![
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        ])
+    }
+
+    fn take_from_bank(state: &mut CurrentTurnState, pyramid: Pyramid) {
+        let bank = &mut state.game_board.bank;
+        match NonZero::new(bank.count(pyramid) - 1) {
+            Some(count) => bank.pyramids.insert(pyramid, count),
+            None => bank.pyramids.remove(&pyramid),
+        };
+    }
+
+    fn create_state(homeworld_names: Vec<Option<String>>) -> CurrentTurnState {
+        let player_count = homeworld_names.len();
+        let mut state = new_game_with_rules(homeworld_names, RuleSet::default()).unwrap();
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+        for player in Player::all(player_count) {
+            let alpha = pyramid(Color::Yellow, Size::Small);
+            let beta = pyramid(Color::Blue, Size::Medium);
+            let starship = pyramid(Color::Green, Size::Large);
+            let homeworld = state.game_board.homeworld_mut(player);
+            homeworld.center = StarSystemCenter::BinaryStar {
+                alpha: Star(alpha),
+                beta: Star(beta),
+            };
+            homeworld
+                .fleet_mut(player)
+                .starships
+                .insert(Starship(starship), NonZero::new(1).unwrap());
+            for pyramid in [alpha, beta, starship] {
+                take_from_bank(&mut state, pyramid);
+            }
+        }
+        state
+    }
+
+    #[test]
+    fn test_game_goes_on() {
+        let state = create_test_state();
+        assert_eq!(check_game_over(&state), None);
+    }
+
+    #[test]
+    fn test_not_checked_during_setup() {
+        let state = new_game(
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        );
+        assert_eq!(check_game_over(&state), None);
+    }
+
+    #[test]
+    fn test_resignation() {
+        let mut state = create_test_state();
+        state.player = Player::SECOND;
+        state.current_turn_status = CurrentTurnStatus::Resigning;
+
+        assert_eq!(
+            check_game_over(&state),
+            Some(GameResult::Win {
+                winner: Player::FIRST,
+                reason: LossReason::Resigned,
+            })
+        );
+    }
+
+    #[test]
+    fn test_homeworld_stars_destroyed() {
+        let mut state = create_test_state();
+        state.game_board.homeworlds[0].center = StarSystemCenter::Empty;
+
+        assert_eq!(
+            check_game_over(&state),
+            Some(GameResult::Win {
+                winner: Player::SECOND,
+                reason: LossReason::HomeworldStarsDestroyed,
+            })
+        );
+    }
+
+    #[test]
+    fn test_homeworld_starships_lost() {
+        let mut state = create_test_state();
+        let homeworld = &mut state.game_board.homeworlds[1];
+        homeworld.fleets[1] = Fleet::default();
+        // Opponent starships at the homeworld don't count
+        homeworld.fleets[0].starships.insert(
+            Starship(pyramid(Color::Red, Size::Large)),
+            NonZero::new(1).unwrap(),
+        );
+
+        assert_eq!(
+            check_game_over(&state),
+            Some(GameResult::Win {
+                winner: Player::FIRST,
+                reason: LossReason::HomeworldStarshipsLost,
+            })
+        );
+    }
+
+    #[test]
+    fn test_both_players_lose() {
+        let mut state = create_test_state();
+        state.game_board.homeworlds[0].center = StarSystemCenter::Empty;
+        state.game_board.homeworlds[1].fleets[1] = Fleet::default();
+
+        assert_eq!(
+            check_game_over(&state),
+            Some(GameResult::Draw {
+                reason: DrawReason::AllHomeworldsLost,
+            })
+        );
+    }
+
+    #[test]
+    fn test_elimination_in_three_player_game() {
+        let mut state = create_state(vec![None, None, None]);
+        state.game_board.homeworlds[1].center = StarSystemCenter::Empty;
+
+        // The other two players play on
+        assert_eq!(
+            losers(&state),
+            vec![(Player::SECOND, LossReason::HomeworldStarsDestroyed)]
+        );
+        assert_eq!(check_game_over(&state), None);
+
+        // Eliminated players don't lose again, the game ends once one player is left
+        state.eliminated.push(Player::SECOND);
+        state.player = Player(2);
+        state.current_turn_status = CurrentTurnStatus::Resigning;
+        assert_eq!(losers(&state), vec![(Player(2), LossReason::Resigned)]);
+        assert_eq!(
+            check_game_over(&state),
+            Some(GameResult::Win {
+                winner: Player::FIRST,
+                reason: LossReason::Resigned,
+            })
+        );
+    }
+
+    #[test]
+    fn test_end_of_turn_operations() {
+        let mut state = create_state(vec![None, None, None]);
+        let third = Player(2);
+        let ship = |color| Starship(pyramid(color, Size::Small));
+        let fleet = |starship| Fleet {
+            starships: [(starship, NonZero::new(1).unwrap())].into(),
+        };
+        // The third player lost the ships at their homeworld to the first player
+        state.game_board.homeworlds[2].fleets =
+            vec![fleet(ship(Color::Red)), Fleet::default(), Fleet::default()];
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(pyramid(Color::Green, Size::Large), NonZero::new(1).unwrap());
+        state.game_board.homeworlds[1].fleets[2] = fleet(ship(Color::Blue));
+        state.game_board.discovered_systems.push(StarSystem {
+            id: SystemId(3),
+            name: None,
+            center: StarSystemCenter::SingleStar(Star(pyramid(Color::Red, Size::Large))),
+            fleets: vec![
+                Fleet::default(),
+                Fleet::default(),
+                fleet(ship(Color::Green)),
+            ],
+            is_homeworld_for: None,
+        });
+        state.game_board.next_system_id = SystemId(4);
+        for placed in [
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Small),
+            pyramid(Color::Green, Size::Small),
+            pyramid(Color::Red, Size::Large),
+        ] {
+            take_from_bank(&mut state, placed);
+        }
+
+        let operations = end_of_turn_operations(&state);
+        assert!(matches!(
+            operations[operations.len() - 2..],
+            [
+                BasicOperation::EliminatePlayer(EliminatePlayer { player: Player(2) }),
+                BasicOperation::EndTurn(_),
+            ]
+        ));
+        apply_all(&mut state, operations).unwrap();
+
+        // None of their starships are left, systems only they occupied are forgotten
+        assert_eq!(state.eliminated, vec![third]);
+        assert_eq!(state.player, Player::SECOND);
+        let board = &state.game_board;
+        assert!(
+            board
+                .star_systems()
+                .all(|system| system.fleet(third).is_empty())
+        );
+        assert!(board.discovered_systems.is_empty());
+        assert!(!board.homeworld(Player::SECOND).is_empty());
+        // The attacker keeps its starship and the stars of the homeworld it occupies
+        let homeworld = board.homeworld(third);
+        assert!(homeworld.fleet(Player::FIRST).contains(ship(Color::Red)));
+        assert!(matches!(
+            homeworld.center,
+            StarSystemCenter::BinaryStar { .. }
+        ));
+        for (pyramid, count) in [
+            (pyramid(Color::Yellow, Size::Small), 0),
+            (pyramid(Color::Blue, Size::Medium), 0),
+            (pyramid(Color::Green, Size::Large), 1),
+            (pyramid(Color::Red, Size::Small), 2),
+            (pyramid(Color::Blue, Size::Small), 3),
+            (pyramid(Color::Green, Size::Small), 3),
+            (pyramid(Color::Red, Size::Large), 3),
+        ] {
+            assert_eq!(board.bank.count(pyramid), count);
+        }
+    }
+
+    #[test]
+    fn test_eliminated_homeworld_without_starships() {
+        let mut state = create_state(vec![None, None, None]);
+        let third = Player(2);
+        state.game_board.homeworlds[2].fleets[2] = Fleet::default();
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(pyramid(Color::Green, Size::Large), NonZero::new(1).unwrap());
+
+        let operations = end_of_turn_operations(&state);
+        apply_all(&mut state, operations).unwrap();
+
+        // Nobody is left at the homeworld, so its stars go back to the bank
+        assert_eq!(state.eliminated, vec![third]);
+        let board = &state.game_board;
+        assert_eq!(board.homeworld(third).center, StarSystemCenter::Empty);
+        assert_eq!(board.bank.count(pyramid(Color::Yellow, Size::Small)), 1);
+        assert_eq!(board.bank.count(pyramid(Color::Blue, Size::Medium)), 1);
+    }
+}
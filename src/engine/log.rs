@@ -0,0 +1,276 @@
+use super::operations::{
+    BasicOperation,
+    transaction::{TransactionError, apply_all},
+};
+use crate::public::current_turn::CurrentTurnState;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Default)]
+pub struct LoggedTurn {
+    pub actions: Vec<Vec<BasicOperation>>,
+    // Empty until the turn is over
+    pub end_of_turn: Vec<BasicOperation>,
+}
+
+// Every change to the game as a stream of operations, starting from the initial state.
+// Homeworld setups are logged as turns of their own.
+#[derive(Clone, Debug)]
+pub struct GameLog {
+    pub initial_state: CurrentTurnState,
+    // The last turn is the one in progress
+    pub turns: Vec<LoggedTurn>,
+}
+
+// Points right after the given number of actions of the given turn
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct LogPosition {
+    pub turn: usize,
+    pub actions: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("position {position:?} is outside of the log")]
+    PositionOutOfRange { position: LogPosition },
+    #[error("cannot replay action {action} of turn {turn}")]
+    ActionFailed {
+        turn: usize,
+        action: usize,
+        #[source]
+        error: TransactionError,
+    },
+    #[error("cannot replay the end of turn {turn}")]
+    EndOfTurnFailed {
+        turn: usize,
+        #[source]
+        error: TransactionError,
+    },
+}
+
+impl GameLog {
+    pub fn new(initial_state: CurrentTurnState) -> Self {
+        GameLog {
+            initial_state,
+            turns: vec![LoggedTurn::default()],
+        }
+    }
+
+    pub fn record_action(&mut self, operations: Vec<BasicOperation>) {
+        self.current_turn_mut().actions.push(operations);
+    }
+
+    pub fn record_end_of_turn(&mut self, operations: Vec<BasicOperation>) {
+        self.current_turn_mut().end_of_turn = operations;
+        self.turns.push(LoggedTurn::default());
+    }
+
+    pub fn end(&self) -> LogPosition {
+        LogPosition {
+            turn: self.turns.len() - 1,
+            actions: self.turns.last().map_or(0, |turn| turn.actions.len()),
+        }
+    }
+
+    pub fn replay(&self) -> Result<CurrentTurnState, ReplayError> {
+        self.replay_until(self.end())
+    }
+
+    pub fn replay_until(&self, position: LogPosition) -> Result<CurrentTurnState, ReplayError> {
+        self.check_position(position)?;
+        let mut state = self.initial_state.clone();
+        for (turn, logged_turn) in self.turns[..=position.turn].iter().enumerate() {
+            let actions = if turn == position.turn {
+                &logged_turn.actions[..position.actions]
+            } else {
+                &logged_turn.actions[..]
+            };
+            for (action, operations) in actions.iter().enumerate() {
+                apply_all(&mut state, operations.clone()).map_err(|error| {
+                    ReplayError::ActionFailed {
+                        turn,
+                        action,
+                        error,
+                    }
+                })?;
+            }
+            if turn != position.turn {
+                apply_all(&mut state, logged_turn.end_of_turn.clone())
+                    .map_err(|error| ReplayError::EndOfTurnFailed { turn, error })?;
+            }
+        }
+        Ok(state)
+    }
+
+    // A copy of the log which continues from the given position
+    pub fn fork(&self, position: LogPosition) -> Result<GameLog, ReplayError> {
+        self.check_position(position)?;
+        let mut turns = self.turns[..=position.turn].to_vec();
+        let last = turns.last_mut().unwrap();
+        last.actions.truncate(position.actions);
+        last.end_of_turn.clear();
+        Ok(GameLog {
+            initial_state: self.initial_state.clone(),
+            turns,
+        })
+    }
+
+    fn check_position(&self, position: LogPosition) -> Result<(), ReplayError> {
+        match self.turns.get(position.turn) {
+            Some(turn) if position.actions <= turn.actions.len() => Ok(()),
+            _ => Err(ReplayError::PositionOutOfRange { position }),
+        }
+    }
+
+    fn current_turn_mut(&mut self) -> &mut LoggedTurn {
+        self.turns.last_mut().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::{
+        bank::{UpdateBank, UpdateBankError},
+        common::UpdateOneDelta,
+        fleet::UpdateFleet,
+        turn::EndTurn,
+    };
+    use crate::engine::setup::new_game;
+    use crate::public::{board::*, common::*, current_turn::*};
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    // Moves a piece from the bank into the homeworld of the first player
+    fn take_from_bank(pyramid: Pyramid) -> Vec<BasicOperation> {
+        vec![
+            UpdateBank {
+                pyramid,
+                delta: UpdateOneDelta::RemoveOne,
+            }
+            .into(),
+            UpdateFleet {
+                star_system: SystemId::homeworld(Player::FIRST),
+                player: Player::FIRST,
+                starship: Starship(pyramid),
+                delta: UpdateOneDelta::AddOne,
+            }
+            .into(),
+        ]
+    }
+
+    fn create_test_log() -> GameLog {
+        let mut state = new_game(None, None);
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+        let mut log = GameLog::new(state);
+        log.record_action(take_from_bank(pyramid(Color::Red, Size::Small)));
+        log.record_action(take_from_bank(pyramid(Color::Red, Size::Medium)));
+        log.record_end_of_turn(vec![EndTurn.into()]);
+        log.record_action(take_from_bank(pyramid(Color::Blue, Size::Large)));
+        log
+    }
+
+    #[test]
+    fn test_replay() {
+        let log = create_test_log();
+
+        let state = log.replay().unwrap();
+        assert_eq!(state.player, Player::SECOND);
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Small)), 2);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Medium)), 2);
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Large)), 2);
+    }
+
+    #[test]
+    fn test_replay_until() {
+        let log = create_test_log();
+
+        let state = log
+            .replay_until(LogPosition {
+                turn: 0,
+                actions: 1,
+            })
+            .unwrap();
+        assert_eq!(state.player, Player::FIRST);
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Small)), 2);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Medium)), 3);
+
+        let state = log
+            .replay_until(LogPosition {
+                turn: 1,
+                actions: 0,
+            })
+            .unwrap();
+        assert_eq!(state.player, Player::SECOND);
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Blue, Size::Large)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_position_out_of_range() {
+        let log = create_test_log();
+        let position = LogPosition {
+            turn: 0,
+            actions: 3,
+        };
+
+        assert!(matches!(
+            log.replay_until(position),
+            Err(ReplayError::PositionOutOfRange { .. })
+        ));
+        assert!(log.fork(position).is_err());
+    }
+
+    #[test]
+    fn test_fork() {
+        let log = create_test_log();
+        let position = LogPosition {
+            turn: 0,
+            actions: 1,
+        };
+
+        let mut fork = log.fork(position).unwrap();
+        assert_eq!(fork.end(), position);
+        assert_eq!(fork.replay().unwrap(), log.replay_until(position).unwrap());
+
+        // The fork continues independently of the original log
+        fork.record_action(take_from_bank(pyramid(Color::Green, Size::Small)));
+        let state = fork.replay().unwrap();
+        assert_eq!(state.player, Player::FIRST);
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Green, Size::Small)), 2);
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Medium)), 3);
+        assert_eq!(log.turns.len(), 2);
+    }
+
+    #[test]
+    fn test_replay_failure() {
+        let mut log = create_test_log();
+        // Only three pieces of each kind exist, so the fourth one can't be taken
+        for _ in 0..3 {
+            log.record_action(take_from_bank(pyramid(Color::Red, Size::Small)));
+        }
+
+        assert!(matches!(
+            log.replay(),
+            Err(ReplayError::ActionFailed {
+                turn: 1,
+                action: 3,
+                error: TransactionError {
+                    index: 0,
+                    error: crate::engine::operations::OperationError::UpdateBankError(
+                        UpdateBankError::NoPyramidsInBank
+                    ),
+                },
+            })
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use super::operations::{BasicOperation, transaction::apply_all};
+use super::resolver::resolve_action;
+use crate::public::{actions::*, board::*, common::*, current_turn::*};
+use strum::IntoEnumIterator;
+
+pub fn legal_actions(state: &CurrentTurnState) -> Vec<Action> {
+    legal_actions_with_operations(state)
+        .into_iter()
+        .map(|(action, _)| action)
+        .collect()
+}
+
+// A turn can be ended once the player has passed, resigned or used a power
+pub fn can_end_turn(state: &CurrentTurnState) -> bool {
+    state.current_turn_status != CurrentTurnStatus::MakingActions
+        || !matches!(state.pending_powers, PendingPowers::Nil)
+}
+
+// Every sequence of actions after which the turn can be ended, including all
+// follow-ups of sacrifices and catastrophes
+pub fn legal_turns(state: &CurrentTurnState) -> Vec<Vec<Action>> {
+    let mut turns = vec![];
+    for_each_turn(&mut state.clone(), &mut |_, actions| {
+        turns.push(actions.to_vec())
+    });
+    turns
+}
+
+// Calls the visitor with the state after every legal turn. The visitor has to leave
+// the state as it was given, so the search can continue from it.
+pub(super) fn for_each_turn(
+    state: &mut CurrentTurnState,
+    visit: &mut impl FnMut(&mut CurrentTurnState, &[Action]),
+) {
+    collect_turns(state, &mut vec![], visit);
+}
+
+fn collect_turns(
+    state: &mut CurrentTurnState,
+    actions: &mut Vec<Action>,
+    visit: &mut impl FnMut(&mut CurrentTurnState, &[Action]),
+) {
+    if can_end_turn(state) {
+        visit(state, actions);
+    }
+    for (action, operations) in legal_actions_with_operations(state) {
+        let inverses = apply_all(state, operations).expect("resolved actions must apply");
+        actions.push(action);
+        collect_turns(state, actions, visit);
+        actions.pop();
+        apply_all(state, inverses).expect("inverse operations must apply");
+    }
+}
+
+pub(super) fn legal_actions_with_operations(
+    state: &CurrentTurnState,
+) -> Vec<(Action, Vec<BasicOperation>)> {
+    candidate_actions(state)
+        .into_iter()
+        .filter_map(|action| {
+            resolve_action(state, &action)
+                .ok()
+                .map(|operations| (action, operations))
+        })
+        .collect()
+}
+
+// Superset of the legal actions, the resolver decides which of them are allowed
+fn candidate_actions(state: &CurrentTurnState) -> Vec<Action> {
+    if state.current_turn_status != CurrentTurnStatus::MakingActions {
+        return vec![];
+    }
+    let game_board = &state.game_board;
+    // Discovering the same star under another name leads to the same position
+    let discoverable = sorted(game_board.bank.pyramids.keys().copied());
+    let mut actions = vec![];
+    for star_system in game_board.star_systems() {
+        let mut play = |action| {
+            actions.push(Action::Play {
+                star_system: star_system.id,
+                action: Box::new(action),
+            })
+        };
+        for color in Color::iter() {
+            play(ActionInStarSystem::Build { color });
+            play(ActionInStarSystem::DeclareCatastrophe { color });
+        }
+        let own = star_system.fleet(state.player).starships.keys();
+        for pyramid in sorted(own.map(|Starship(pyramid)| *pyramid)) {
+            let starship = Starship(pyramid);
+            for target in game_board.star_systems() {
+                if target.id != star_system.id {
+                    play(ActionInStarSystem::Move {
+                        starship,
+                        target: MoveTargetStarSystem::Known {
+                            star_system: target.id,
+                        },
+                    });
+                }
+            }
+            for star in &discoverable {
+                play(ActionInStarSystem::Move {
+                    starship,
+                    target: MoveTargetStarSystem::Discovered {
+                        name: None,
+                        star: Star(*star),
+                    },
+                });
+            }
+            for new_color in Color::iter() {
+                play(ActionInStarSystem::Trade {
+                    starship,
+                    new_color,
+                });
+            }
+            play(ActionInStarSystem::Sacrifice { starship });
+        }
+        let opponent = star_system.fleet(state.player_on_left(state.player));
+        let opponent = opponent.starships.keys();
+        for pyramid in sorted(opponent.map(|Starship(pyramid)| *pyramid)) {
+            play(ActionInStarSystem::Capture {
+                starship: Starship(pyramid),
+            });
+        }
+    }
+    actions.push(Action::Pass);
+    actions.push(Action::Resign);
+    actions
+}
+
+// Hash map order is random, but the generated actions should be deterministic
+fn sorted(pyramids: impl Iterator<Item = Pyramid>) -> Vec<Pyramid> {
+    let mut pyramids: Vec<Pyramid> = pyramids.collect();
+    pyramids.sort_by_key(|pyramid| (pyramid.color as u8, pyramid.size));
+    pyramids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::Apply;
+    use crate::engine::setup::{HomeworldChoice, new_game, set_up_homeworld};
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        let mut state = new_game(None, None);
+        for (alpha, beta) in [
+            (
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+            ),
+            (
+                pyramid(Color::Yellow, Size::Large),
+                pyramid(Color::Blue, Size::Small),
+            ),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(alpha),
+                beta: Star(beta),
+                starship: Starship(pyramid(Color::Green, Size::Large)),
+            };
+            set_up_homeworld(&mut state, choice).unwrap();
+        }
+        state
+    }
+
+    fn count_moves(actions: &[Action]) -> usize {
+        actions
+            .iter()
+            .filter(|action| {
+                matches!(
+                    action,
+                    Action::Play { action, .. }
+                        if matches!(**action, ActionInStarSystem::Move { .. })
+                )
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_legal_actions() {
+        let state = create_test_state();
+
+        // Build green, trade for three colors, sacrifice, pass and resign
+        let actions = legal_actions(&state);
+        assert_eq!(actions.len(), 7);
+        assert!(actions.contains(&Action::Pass));
+        assert!(actions.contains(&Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Build {
+                color: Color::Green
+            }),
+        }));
+        for action in &actions {
+            assert!(resolve_action(&state, action).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_moves_to_distinct_discoverable_stars() {
+        let mut state = create_test_state();
+        let starship = pyramid(Color::Yellow, Size::Small);
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(starship, NonZero::new(2).unwrap());
+        state.game_board.homeworlds[0].fleets[0]
+            .starships
+            .insert(Starship(starship), NonZero::new(1).unwrap());
+
+        // Only large stars can be discovered, and the other homeworld is not connected
+        let actions = legal_actions(&state);
+        assert_eq!(count_moves(&actions), 2 * Color::iter().count());
+    }
+
+    #[test]
+    fn test_no_actions_outside_of_turn() {
+        let mut state = create_test_state();
+        state.current_turn_status = CurrentTurnStatus::Passing;
+
+        assert!(legal_actions(&state).is_empty());
+        assert_eq!(legal_turns(&state), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_legal_turns() {
+        let state = create_test_state();
+
+        // Pass, resign, and four actions and a sacrifice, each of them optionally followed
+        // by a resignation. Nothing can be built with the powers of the sacrifice.
+        let turns = legal_turns(&state);
+        assert_eq!(turns.len(), 12);
+        assert!(turns.contains(&vec![Action::Pass]));
+        assert!(turns.iter().all(|turn| !turn.is_empty()));
+    }
+
+    #[test]
+    fn test_legal_turns_with_sacrifice() {
+        let mut state = create_test_state();
+        let homeworld = &mut state.game_board.homeworlds[0];
+        for starship in [
+            pyramid(Color::Green, Size::Small),
+            pyramid(Color::Green, Size::Medium),
+        ] {
+            homeworld.fleets[0]
+                .starships
+                .insert(Starship(starship), NonZero::new(1).unwrap());
+            state
+                .game_board
+                .bank
+                .pyramids
+                .insert(starship, NonZero::new(2).unwrap());
+        }
+        let sacrifice = Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Sacrifice {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+            }),
+        };
+
+        let build = Action::Play {
+            star_system: SystemId::homeworld(Player::FIRST),
+            action: Box::new(ActionInStarSystem::Build {
+                color: Color::Green,
+            }),
+        };
+
+        let turns = legal_turns(&state);
+        assert!(turns.contains(&vec![sacrifice.clone()]));
+        assert!(turns.contains(&vec![sacrifice.clone(), build.clone()]));
+        assert!(turns.contains(&vec![sacrifice, build.clone(), build]));
+
+        // Every turn can be played from the original state and ended afterwards
+        for turn in &turns {
+            let mut replayed = state.clone();
+            for action in turn {
+                for operation in resolve_action(&replayed, action).unwrap() {
+                    operation.apply(&mut replayed).unwrap();
+                }
+            }
+            assert!(can_end_turn(&replayed));
+        }
+    }
+}
@@ -1,10 +1,12 @@
-mod bank;
-mod common;
-mod fleet;
-mod pending_powers;
-mod stars;
-mod systems;
-mod turn;
+pub mod bank;
+pub mod common;
+pub mod fleet;
+pub mod pending_powers;
+pub mod stars;
+pub mod systems;
+pub mod trade;
+pub mod transaction;
+pub mod turn;
 mod utils;
 
 use crate::public::*;
@@ -14,27 +16,50 @@ use thiserror::Error;
 use bank::UpdateBank;
 use fleet::UpdateFleet;
 use pending_powers::UpdatePendingPowers;
-use stars::DestroyStar;
-use systems::{DiscoverSystem, ForgetSystem};
+use stars::{DestroyStar, RestoreStar};
+use systems::{
+    DiscoverSystem, ForgetSystem, RestoreSystem, SetUpHomeworld, TearDownHomeworld,
+    UndiscoverSystem,
+};
+use trade::TradeStarship;
+use turn::{EliminatePlayer, EndTurn, ReinstatePlayer, RestoreTurn, SetCurrentTurnStatus};
 
 #[enum_dispatch]
-enum BasicOperation {
+#[derive(Clone, Debug)]
+pub enum BasicOperation {
     DiscoverSystem,
     ForgetSystem,
     UpdatePendingPowers,
     UpdateFleet,
     UpdateBank,
     DestroyStar,
+    SetCurrentTurnStatus,
+    EndTurn,
+    SetUpHomeworld,
+    TradeStarship,
+    RestoreStar,
+    UndiscoverSystem,
+    RestoreSystem,
+    TearDownHomeworld,
+    RestoreTurn,
+    EliminatePlayer,
+    ReinstatePlayer,
 }
 
 #[derive(Error, Debug)]
-enum OperationError {
+pub enum OperationError {
     #[error("star system with name {name:?} already exists")]
     DuplicatedStarSystemName { name: String },
+    #[error("star system with id {id:?} already exists")]
+    DuplicatedStarSystemId { id: board::SystemId },
     #[error("cannot update pending powers")]
     UpdatePendingPowersError(#[from] pending_powers::UpdatePendingPowersError),
-    #[error("unknown star system")]
-    UnknownStarSystem,
+    #[error("unknown star system {id:?}")]
+    UnknownStarSystem { id: board::SystemId },
+    #[error("unknown player {player:?}")]
+    UnknownPlayer {
+        player: crate::public::common::Player,
+    },
     #[error("cannot update fleet")]
     UpdateFleetError(#[from] fleet::UpdateFleetError),
     #[error("cannot update bank")]
@@ -43,11 +68,27 @@ enum OperationError {
     ForgetSystemError(#[from] systems::ForgetSystemError),
     #[error("cannot destroy star")]
     DestroyStarError(#[from] stars::DestroyStarError),
+    #[error("cannot restore star")]
+    RestoreStarError(#[from] stars::RestoreStarError),
+    #[error("cannot set up homeworld")]
+    SetUpHomeworldError(#[from] systems::SetUpHomeworldError),
+    #[error("cannot tear down homeworld")]
+    TearDownHomeworldError(#[from] systems::TearDownHomeworldError),
+    #[error("cannot trade starship")]
+    TradeStarshipError(#[from] trade::TradeStarshipError),
     #[error("cannot update current turn status")]
     SetCurrentTurnStatusError(#[from] turn::SetCurrentTurnStatusError),
+    #[error("cannot end turn")]
+    EndTurnError(#[from] turn::EndTurnError),
+    #[error("cannot eliminate player")]
+    EliminatePlayerError(#[from] turn::EliminatePlayerError),
 }
 
+// A successful apply returns the inverse operation, which restores the state from before it
 #[enum_dispatch(BasicOperation)]
-trait Apply {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError>;
+pub trait Apply {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError>;
 }
@@ -1,9 +1,11 @@
 use super::common::UpdateOneDelta;
 use super::utils;
-use super::{Apply, OperationError};
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
 use crate::public::*;
 use thiserror::Error;
 
+#[derive(Clone, Debug)]
 pub struct UpdateBank {
     pub pyramid: common::Pyramid,
     pub delta: UpdateOneDelta,
@@ -18,8 +20,12 @@ pub enum UpdateBankError {
 }
 
 impl Apply for UpdateBank {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
         let bank = &mut state.game_board.bank;
+        let old_count = bank.count(self.pyramid);
         let entry = bank.pyramids.entry(self.pyramid);
         utils::update_hashmap_count(
             entry,
@@ -27,7 +33,16 @@ impl Apply for UpdateBank {
             UpdateBankError::BankCountOverflow,
             UpdateBankError::NoPyramidsInBank,
         )?;
-        Ok(())
+        zobrist::update(
+            &mut state.hash,
+            zobrist::bank_key(self.pyramid, old_count),
+            zobrist::bank_key(self.pyramid, bank.count(self.pyramid)),
+        );
+        Ok(UpdateBank {
+            pyramid: self.pyramid,
+            delta: self.delta.inverse(),
+        }
+        .into())
     }
 }
 
@@ -40,27 +55,33 @@ mod tests {
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: board::SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: board::SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: board::SystemId(2),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         }
@@ -186,4 +207,24 @@ mod tests {
             ))
         ));
     }
+
+    #[test]
+    fn test_inverse_restores_bank() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let op = UpdateBank {
+            pyramid: common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            },
+            delta: UpdateOneDelta::AddOne,
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        let after = state.clone();
+        let redo = inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+        redo.apply(&mut state).unwrap();
+        assert_eq!(state, after);
+    }
 }
@@ -1,4 +1,14 @@
+#[derive(Clone, Copy, Debug)]
 pub enum UpdateOneDelta {
     AddOne,
     RemoveOne,
 }
+
+impl UpdateOneDelta {
+    pub fn inverse(self) -> UpdateOneDelta {
+        match self {
+            UpdateOneDelta::AddOne => UpdateOneDelta::RemoveOne,
+            UpdateOneDelta::RemoveOne => UpdateOneDelta::AddOne,
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use super::common;
-use super::{Apply, OperationError};
-use crate::public::{board::Starship, common::Player, current_turn::CurrentTurnState};
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
+use crate::public::{
+    board::{Starship, SystemId},
+    common::Player,
+    current_turn::CurrentTurnState,
+};
 use thiserror::Error;
 
+#[derive(Clone, Debug)]
 pub struct UpdateFleet {
-    pub star_system_name: String,
+    pub star_system: SystemId,
     pub player: Player,
     pub starship: Starship,
     pub delta: common::UpdateOneDelta,
@@ -19,15 +25,14 @@ pub enum UpdateFleetError {
 }
 
 impl Apply for UpdateFleet {
-    fn apply(self, state: &mut CurrentTurnState) -> Result<(), OperationError> {
-        let Some(star_system) = state
-            .game_board
-            .discovered_systems
-            .iter_mut()
-            .find(|it| it.name == self.star_system_name)
-        else {
-            return Err(OperationError::UnknownStarSystem);
+    fn apply(self, state: &mut CurrentTurnState) -> Result<BasicOperation, OperationError> {
+        super::utils::check_player(&state.game_board, self.player)?;
+        let Some(star_system) = state.game_board.star_system_mut(self.star_system) else {
+            return Err(OperationError::UnknownStarSystem {
+                id: self.star_system,
+            });
         };
+        let old_hash = zobrist::system_hash(star_system);
         let fleet = star_system.fleet_mut(self.player);
         let entry = fleet.starships.entry(self.starship);
         super::utils::update_hashmap_count(
@@ -36,7 +41,12 @@ impl Apply for UpdateFleet {
             UpdateFleetError::FleetCountOverflow,
             UpdateFleetError::NoSuchStarships,
         )?;
-        Ok(())
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(star_system));
+        Ok(UpdateFleet {
+            delta: self.delta.inverse(),
+            ..self
+        }
+        .into())
     }
 }
 
@@ -49,35 +59,41 @@ mod tests {
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         let mut state = current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: SystemId(3),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         };
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Alpha".to_string(),
+            id: SystemId(2),
+            name: Some("Alpha".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
             is_homeworld_for: None,
         });
         state
@@ -92,8 +108,8 @@ mod tests {
         });
 
         let op = UpdateFleet {
-            star_system_name: "Alpha".to_string(),
-            player: Player::First,
+            star_system: SystemId(2),
+            player: Player::FIRST,
             starship,
             delta: UpdateOneDelta::AddOne,
         };
@@ -101,8 +117,7 @@ mod tests {
         let result = op.apply(&mut state);
         assert!(result.is_ok());
         assert_eq!(
-            state.game_board.discovered_systems[0]
-                .fleet_first
+            state.game_board.discovered_systems[0].fleets[0]
                 .starships
                 .get(&starship)
                 .unwrap()
@@ -119,14 +134,13 @@ mod tests {
             color: common::Color::Red,
         });
 
-        state.game_board.discovered_systems[0]
-            .fleet_first
+        state.game_board.discovered_systems[0].fleets[0]
             .starships
             .insert(starship, NonZero::new(1).unwrap());
 
         let op = UpdateFleet {
-            star_system_name: "Alpha".to_string(),
-            player: Player::First,
+            star_system: SystemId(2),
+            player: Player::FIRST,
             starship,
             delta: UpdateOneDelta::AddOne,
         };
@@ -134,8 +148,7 @@ mod tests {
         let result = op.apply(&mut state);
         assert!(result.is_ok());
         assert_eq!(
-            state.game_board.discovered_systems[0]
-                .fleet_first
+            state.game_board.discovered_systems[0].fleets[0]
                 .starships
                 .get(&starship)
                 .unwrap()
@@ -152,14 +165,13 @@ mod tests {
             color: common::Color::Red,
         });
 
-        state.game_board.discovered_systems[0]
-            .fleet_first
+        state.game_board.discovered_systems[0].fleets[0]
             .starships
             .insert(starship, NonZero::new(2).unwrap());
 
         let op = UpdateFleet {
-            star_system_name: "Alpha".to_string(),
-            player: Player::First,
+            star_system: SystemId(2),
+            player: Player::FIRST,
             starship,
             delta: UpdateOneDelta::RemoveOne,
         };
@@ -167,8 +179,7 @@ mod tests {
         let result = op.apply(&mut state);
         assert!(result.is_ok());
         assert_eq!(
-            state.game_board.discovered_systems[0]
-                .fleet_first
+            state.game_board.discovered_systems[0].fleets[0]
                 .starships
                 .get(&starship)
                 .unwrap()
@@ -185,14 +196,13 @@ mod tests {
             color: common::Color::Red,
         });
 
-        state.game_board.discovered_systems[0]
-            .fleet_first
+        state.game_board.discovered_systems[0].fleets[0]
             .starships
             .insert(starship, NonZero::new(1).unwrap());
 
         let op = UpdateFleet {
-            star_system_name: "Alpha".to_string(),
-            player: Player::First,
+            star_system: SystemId(2),
+            player: Player::FIRST,
             starship,
             delta: UpdateOneDelta::RemoveOne,
         };
@@ -200,8 +210,7 @@ mod tests {
         let result = op.apply(&mut state);
         assert!(result.is_ok());
         assert!(
-            !state.game_board.discovered_systems[0]
-                .fleet_first
+            !state.game_board.discovered_systems[0].fleets[0]
                 .starships
                 .contains_key(&starship)
         );
@@ -216,8 +225,8 @@ mod tests {
         });
 
         let op = UpdateFleet {
-            star_system_name: "Alpha".to_string(),
-            player: Player::First,
+            star_system: SystemId(2),
+            player: Player::FIRST,
             starship,
             delta: UpdateOneDelta::RemoveOne,
         };
@@ -240,13 +249,83 @@ mod tests {
         });
 
         let op = UpdateFleet {
-            star_system_name: "Unknown".to_string(),
-            player: Player::First,
+            star_system: SystemId(99),
+            player: Player::FIRST,
+            starship,
+            delta: UpdateOneDelta::AddOne,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownStarSystem { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_player() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let starship = Starship(common::Pyramid {
+            size: common::Size::Small,
+            color: common::Color::Red,
+        });
+
+        let op = UpdateFleet {
+            star_system: SystemId(2),
+            player: Player(2),
             starship,
             delta: UpdateOneDelta::AddOne,
         };
 
         let result = op.apply(&mut state);
-        assert!(matches!(result, Err(OperationError::UnknownStarSystem)));
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownPlayer { player: Player(2) })
+        ));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_update_homeworld_fleet() {
+        let mut state = create_test_state();
+        let starship = Starship(common::Pyramid {
+            size: common::Size::Large,
+            color: common::Color::Green,
+        });
+
+        let op = UpdateFleet {
+            star_system: SystemId(1),
+            player: Player::FIRST,
+            starship,
+            delta: UpdateOneDelta::AddOne,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(result.is_ok());
+        assert!(state.game_board.homeworlds[1].fleets[0].contains(starship));
+    }
+
+    #[test]
+    fn test_inverse_restores_fleet() {
+        let mut state = create_test_state();
+        let starship = Starship(common::Pyramid {
+            size: common::Size::Small,
+            color: common::Color::Red,
+        });
+        state.game_board.discovered_systems[0].fleets[1]
+            .starships
+            .insert(starship, std::num::NonZero::new(2).unwrap());
+        let before = state.clone();
+        let op = UpdateFleet {
+            star_system: SystemId(2),
+            player: Player::SECOND,
+            starship,
+            delta: UpdateOneDelta::RemoveOne,
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
     }
 }
@@ -1,15 +1,18 @@
 use std::{mem::replace, num::NonZero};
 
-use super::{Apply, OperationError};
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
 use crate::public::{current_turn::PendingPowers, *};
 use thiserror::Error;
 
+#[derive(Clone, Debug)]
 pub enum UpdatePendingPowers {
     Set {
         power: common::Power,
         count: NonZero<u8>,
     },
     UseOne,
+    Restore(PendingPowers),
 }
 
 #[derive(Error, Debug)]
@@ -23,8 +26,13 @@ pub enum UpdatePendingPowersError {
 }
 
 impl Apply for UpdatePendingPowers {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
-        let mut pending_powers = replace(&mut state.pending_powers, PendingPowers::Nil);
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let previous = state.pending_powers;
+        let old_hash = zobrist::turn_hash(state);
+        let mut pending_powers = previous;
         state.pending_powers = match self {
             UpdatePendingPowers::Set { power, count } => match pending_powers {
                 PendingPowers::Nil => PendingPowers::Pending {
@@ -58,8 +66,11 @@ impl Apply for UpdatePendingPowers {
                     return Err(UpdatePendingPowersError::AlreadyExhausted.into());
                 }
             },
+
+            UpdatePendingPowers::Restore(pending_powers) => pending_powers,
         };
-        Ok(())
+        zobrist::update_turn(state, old_hash);
+        Ok(UpdatePendingPowers::Restore(previous).into())
     }
 }
 
@@ -71,27 +82,33 @@ mod tests {
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: board::SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: board::SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: board::SystemId(2),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         }
@@ -205,4 +222,22 @@ mod tests {
             ))
         ));
     }
+
+    #[test]
+    fn test_inverse_restores_pending_powers() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let op = UpdatePendingPowers::Set {
+            power: common::Power::Move,
+            count: NonZero::new(1).unwrap(),
+        };
+        let inverse_set = op.apply(&mut state).unwrap();
+        let pending = state.clone();
+        let inverse_use = UpdatePendingPowers::UseOne.apply(&mut state).unwrap();
+
+        inverse_use.apply(&mut state).unwrap();
+        assert_eq!(state, pending);
+        inverse_set.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+    }
 }
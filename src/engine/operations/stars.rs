@@ -1,16 +1,17 @@
-use std::mem::replace;
-
-use super::{Apply, OperationError};
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
 use crate::public::*;
 use thiserror::Error;
 
+#[derive(Clone, Copy, Debug)]
 pub enum DestroyStarSelector {
     Binary(board::BinaryStarId),
     Single,
 }
 
+#[derive(Clone, Debug)]
 pub struct DestroyStar {
-    pub star_system_name: String,
+    pub star_system: board::SystemId,
     pub star: DestroyStarSelector,
 }
 
@@ -25,39 +26,38 @@ pub enum DestroyStarError {
 }
 
 impl Apply for DestroyStar {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
-        let Some(system) = state
-            .game_board
-            .discovered_systems
-            .iter_mut()
-            .find(|it| it.name == self.star_system_name)
-        else {
-            return Err(OperationError::UnknownStarSystem);
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let Some(system) = state.game_board.star_system_mut(self.star_system) else {
+            return Err(OperationError::UnknownStarSystem {
+                id: self.star_system,
+            });
         };
 
-        let old_center = replace(&mut system.center, board::StarSystemCenter::Empty);
-
-        system.center = match self.star {
-            DestroyStarSelector::Binary(star_id) => {
-                match old_center {
-                    board::StarSystemCenter::BinaryStar { alpha, beta } => {
-                        // Keep the star that wasn't destroyed
-                        let remaining_star = match star_id {
-                            board::BinaryStarId::Alpha => beta,
-                            board::BinaryStarId::Beta => alpha,
-                        };
-                        board::StarSystemCenter::SingleStar(remaining_star)
-                    }
-                    board::StarSystemCenter::SingleStar(_) => {
-                        return Err(DestroyStarError::NotABinarySystem.into());
-                    }
-                    board::StarSystemCenter::Empty => {
-                        return Err(DestroyStarError::CenterAlreadyEmpty.into());
+        let (new_center, destroyed_star) = match self.star {
+            DestroyStarSelector::Binary(star_id) => match system.center {
+                board::StarSystemCenter::BinaryStar { alpha, beta } => {
+                    // Keep the star that wasn't destroyed
+                    match star_id {
+                        board::BinaryStarId::Alpha => {
+                            (board::StarSystemCenter::SingleStar(beta), alpha)
+                        }
+                        board::BinaryStarId::Beta => {
+                            (board::StarSystemCenter::SingleStar(alpha), beta)
+                        }
                     }
                 }
-            }
-            DestroyStarSelector::Single => match old_center {
-                board::StarSystemCenter::SingleStar(_) => board::StarSystemCenter::Empty,
+                board::StarSystemCenter::SingleStar(_) => {
+                    return Err(DestroyStarError::NotABinarySystem.into());
+                }
+                board::StarSystemCenter::Empty => {
+                    return Err(DestroyStarError::CenterAlreadyEmpty.into());
+                }
+            },
+            DestroyStarSelector::Single => match system.center {
+                board::StarSystemCenter::SingleStar(star) => (board::StarSystemCenter::Empty, star),
                 board::StarSystemCenter::BinaryStar { .. } => {
                     return Err(DestroyStarError::NotASingleStarSystem.into());
                 }
@@ -66,7 +66,76 @@ impl Apply for DestroyStar {
                 }
             },
         };
-        Ok(())
+        let old_hash = zobrist::system_hash(system);
+        system.center = new_center;
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(system));
+        Ok(RestoreStar {
+            star_system: self.star_system,
+            star: self.star,
+            destroyed_star,
+        }
+        .into())
+    }
+}
+
+// Puts a destroyed star back where the selector took it from
+#[derive(Clone, Debug)]
+pub struct RestoreStar {
+    pub star_system: board::SystemId,
+    pub star: DestroyStarSelector,
+    pub destroyed_star: board::Star,
+}
+
+#[derive(Error, Debug)]
+pub enum RestoreStarError {
+    #[error("can only restore a binary star next to a single star")]
+    NotASingleStarSystem,
+    #[error("can only restore a single star into an empty center")]
+    CenterNotEmpty,
+}
+
+impl Apply for RestoreStar {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let Some(system) = state.game_board.star_system_mut(self.star_system) else {
+            return Err(OperationError::UnknownStarSystem {
+                id: self.star_system,
+            });
+        };
+
+        let old_hash = zobrist::system_hash(system);
+        system.center = match (self.star, &system.center) {
+            (
+                DestroyStarSelector::Binary(star_id),
+                board::StarSystemCenter::SingleStar(remaining),
+            ) => match star_id {
+                board::BinaryStarId::Alpha => board::StarSystemCenter::BinaryStar {
+                    alpha: self.destroyed_star,
+                    beta: *remaining,
+                },
+                board::BinaryStarId::Beta => board::StarSystemCenter::BinaryStar {
+                    alpha: *remaining,
+                    beta: self.destroyed_star,
+                },
+            },
+            (DestroyStarSelector::Binary(_), _) => {
+                return Err(RestoreStarError::NotASingleStarSystem.into());
+            }
+            (DestroyStarSelector::Single, board::StarSystemCenter::Empty) => {
+                board::StarSystemCenter::SingleStar(self.destroyed_star)
+            }
+            (DestroyStarSelector::Single, _) => {
+                return Err(RestoreStarError::CenterNotEmpty.into());
+            }
+        };
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(system));
+        Ok(DestroyStar {
+            star_system: self.star_system,
+            star: self.star,
+        }
+        .into())
     }
 }
 
@@ -77,35 +146,41 @@ mod tests {
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         let mut state = current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: board::SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: board::SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: board::SystemId(3),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         };
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
             is_homeworld_for: None,
         });
         state
@@ -127,7 +202,7 @@ mod tests {
             board::StarSystemCenter::BinaryStar { alpha, beta };
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Binary(board::BinaryStarId::Alpha),
         };
 
@@ -155,7 +230,7 @@ mod tests {
             board::StarSystemCenter::BinaryStar { alpha, beta };
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Binary(board::BinaryStarId::Beta),
         };
 
@@ -177,7 +252,7 @@ mod tests {
             }));
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Single,
         };
 
@@ -199,7 +274,7 @@ mod tests {
             }));
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Binary(board::BinaryStarId::Alpha),
         };
 
@@ -228,7 +303,7 @@ mod tests {
             board::StarSystemCenter::BinaryStar { alpha, beta };
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Single,
         };
 
@@ -246,7 +321,7 @@ mod tests {
         let mut state = create_test_state();
 
         let op = DestroyStar {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
             star: DestroyStarSelector::Single,
         };
 
@@ -264,11 +339,100 @@ mod tests {
         let mut state = create_test_state();
 
         let op = DestroyStar {
-            star_system_name: "Unknown".to_string(),
+            star_system: board::SystemId(99),
             star: DestroyStarSelector::Single,
         };
 
         let result = op.apply(&mut state);
-        assert!(matches!(result, Err(OperationError::UnknownStarSystem)));
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownStarSystem { .. })
+        ));
+    }
+
+    #[test]
+    fn test_destroy_homeworld_star() {
+        let mut state = create_test_state();
+        let beta = board::Star(common::Pyramid {
+            size: common::Size::Medium,
+            color: common::Color::Blue,
+        });
+        state.game_board.homeworlds[0].center = board::StarSystemCenter::BinaryStar {
+            alpha: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+            beta,
+        };
+
+        let op = DestroyStar {
+            star_system: board::SystemId(0),
+            star: DestroyStarSelector::Binary(board::BinaryStarId::Alpha),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(result.is_ok());
+        assert!(matches!(
+            state.game_board.homeworlds[0].center,
+            board::StarSystemCenter::SingleStar(star) if star == beta
+        ));
+    }
+
+    #[test]
+    fn test_inverse_restores_destroyed_star() {
+        for star in [
+            DestroyStarSelector::Binary(board::BinaryStarId::Alpha),
+            DestroyStarSelector::Binary(board::BinaryStarId::Beta),
+        ] {
+            let mut state = create_test_state();
+            state.game_board.discovered_systems[0].center = board::StarSystemCenter::BinaryStar {
+                alpha: board::Star(common::Pyramid {
+                    size: common::Size::Small,
+                    color: common::Color::Red,
+                }),
+                beta: board::Star(common::Pyramid {
+                    size: common::Size::Large,
+                    color: common::Color::Blue,
+                }),
+            };
+            let before = state.clone();
+            let op = DestroyStar {
+                star_system: board::SystemId(2),
+                star,
+            };
+
+            let inverse = op.apply(&mut state).unwrap();
+            let after = state.clone();
+            let redo = inverse.apply(&mut state).unwrap();
+            assert_eq!(state, before);
+            redo.apply(&mut state).unwrap();
+            assert_eq!(state, after);
+        }
+    }
+
+    #[test]
+    fn test_restore_star_into_occupied_center() {
+        let mut state = create_test_state();
+        state.game_board.discovered_systems[0].center =
+            board::StarSystemCenter::SingleStar(board::Star(common::Pyramid {
+                size: common::Size::Large,
+                color: common::Color::Blue,
+            }));
+        let op = RestoreStar {
+            star_system: board::SystemId(2),
+            star: DestroyStarSelector::Single,
+            destroyed_star: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::RestoreStarError(
+                RestoreStarError::CenterNotEmpty
+            ))
+        ));
     }
 }
@@ -1,35 +1,86 @@
-use super::{Apply, OperationError};
+use super::bank::UpdateBankError;
+use super::common::UpdateOneDelta;
+use super::utils;
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
 use crate::public::*;
+use std::num::NonZero;
 use thiserror::Error;
 
+#[derive(Clone, Debug)]
 pub struct DiscoverSystem {
-    pub name: String,
+    pub id: board::SystemId,
+    pub name: Option<String>,
     pub center_star: board::Star,
 }
 
 impl Apply for DiscoverSystem {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
         let game_board = &mut state.game_board;
-        if game_board
-            .discovered_systems
-            .iter()
-            .any(|it| it.name == self.name)
+        if game_board.star_system(self.id).is_some() {
+            return Err(OperationError::DuplicatedStarSystemId { id: self.id });
+        }
+        if let Some(name) = &self.name
+            && game_board.star_system_by_name(name).is_some()
         {
-            return Err(OperationError::DuplicatedStarSystemName { name: self.name });
+            return Err(OperationError::DuplicatedStarSystemName { name: name.clone() });
         }
-        game_board.discovered_systems.push(board::StarSystem {
+        let next_system_id = game_board.next_system_id;
+        // Ids are never handed out twice, even after the system is forgotten
+        game_board.next_system_id = next_system_id.max(board::SystemId(self.id.0 + 1));
+        let system = board::StarSystem {
+            id: self.id,
             name: self.name,
             center: board::StarSystemCenter::SingleStar(self.center_star),
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
+            fleets: vec![board::Fleet::default(); game_board.player_count()],
             is_homeworld_for: None,
-        });
-        Ok(())
+        };
+        zobrist::update(&mut state.hash, 0, zobrist::system_hash(&system));
+        game_board.discovered_systems.push(system);
+        Ok(UndiscoverSystem {
+            star_system: self.id,
+            next_system_id,
+        }
+        .into())
+    }
+}
+
+// Takes back a discovery: unlike ForgetSystem the star stays out of the bank
+#[derive(Clone, Debug)]
+pub struct UndiscoverSystem {
+    pub star_system: board::SystemId,
+    pub next_system_id: board::SystemId,
+}
+
+impl Apply for UndiscoverSystem {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let game_board = &mut state.game_board;
+        let system_index = discovered_system_index(game_board, self.star_system)?;
+        let system = &game_board.discovered_systems[system_index];
+        let board::StarSystemCenter::SingleStar(center_star) = system.center else {
+            return Err(ForgetSystemError::NotASingleStarSystem.into());
+        };
+        let system = game_board.discovered_systems.remove(system_index);
+        game_board.next_system_id = self.next_system_id;
+        zobrist::update(&mut state.hash, zobrist::system_hash(&system), 0);
+        Ok(DiscoverSystem {
+            id: system.id,
+            name: system.name,
+            center_star,
+        }
+        .into())
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct ForgetSystem {
-    pub star_system_name: String,
+    pub star_system: board::SystemId,
 }
 
 #[derive(Error, Debug)]
@@ -38,29 +89,212 @@ pub enum ForgetSystemError {
     CannotForgetHomeworld,
     #[error("cannot forget a system with non-empty fleets")]
     FleetsNotEmpty,
+    #[error("cannot undiscover a system without a single star")]
+    NotASingleStarSystem,
 }
 
 impl Apply for ForgetSystem {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
-        let system_index = state
-            .game_board
-            .discovered_systems
-            .iter()
-            .position(|it| it.name == self.star_system_name)
-            .ok_or(OperationError::UnknownStarSystem)?;
-
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let system_index = discovered_system_index(&state.game_board, self.star_system)?;
         let system = &state.game_board.discovered_systems[system_index];
+        if system
+            .center
+            .stars()
+            .any(|board::Star(pyramid)| state.game_board.bank.count(pyramid) == u8::MAX)
+        {
+            return Err(UpdateBankError::BankCountOverflow.into());
+        }
 
-        if system.is_homeworld_for.is_some() {
-            return Err(ForgetSystemError::CannotForgetHomeworld.into());
+        let system = state.game_board.discovered_systems.remove(system_index);
+        zobrist::update(&mut state.hash, zobrist::system_hash(&system), 0);
+        for board::Star(pyramid) in system.center.stars() {
+            let bank = &mut state.game_board.bank;
+            let old_count = bank.count(pyramid);
+            utils::update_hashmap_count(
+                bank.pyramids.entry(pyramid),
+                UpdateOneDelta::AddOne,
+                UpdateBankError::BankCountOverflow,
+                UpdateBankError::NoPyramidsInBank,
+            )?;
+            zobrist::update(
+                &mut state.hash,
+                zobrist::bank_key(pyramid, old_count),
+                zobrist::bank_key(pyramid, old_count + 1),
+            );
         }
+        Ok(RestoreSystem {
+            index: system_index,
+            star_system: system,
+        }
+        .into())
+    }
+}
+
+// Finds a discovered system which has no starships left and can be taken off the board
+fn discovered_system_index(
+    game_board: &board::GameBoard,
+    star_system: board::SystemId,
+) -> Result<usize, OperationError> {
+    let system = game_board
+        .star_system(star_system)
+        .ok_or(OperationError::UnknownStarSystem { id: star_system })?;
+
+    if system.is_homeworld_for.is_some() {
+        return Err(ForgetSystemError::CannotForgetHomeworld.into());
+    }
+
+    if !system.is_empty() {
+        return Err(ForgetSystemError::FleetsNotEmpty.into());
+    }
+
+    let system_index = game_board
+        .discovered_systems
+        .iter()
+        .position(|it| it.id == star_system)
+        .ok_or(ForgetSystemError::CannotForgetHomeworld)?;
+    Ok(system_index)
+}
 
-        if !system.fleet_first.starships.is_empty() || !system.fleet_second.starships.is_empty() {
-            return Err(ForgetSystemError::FleetsNotEmpty.into());
+// Puts a forgotten system back at its old position, taking its stars from the bank again
+#[derive(Clone, Debug)]
+pub struct RestoreSystem {
+    pub index: usize,
+    pub star_system: board::StarSystem,
+}
+
+impl Apply for RestoreSystem {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let game_board = &mut state.game_board;
+        let id = self.star_system.id;
+        if game_board.star_system(id).is_some() {
+            return Err(OperationError::DuplicatedStarSystemId { id });
+        }
+        if let Some(name) = &self.star_system.name
+            && game_board.star_system_by_name(name).is_some()
+        {
+            return Err(OperationError::DuplicatedStarSystemName { name: name.clone() });
+        }
+        let stars: Vec<board::Star> = self.star_system.center.stars().collect();
+        for board::Star(pyramid) in &stars {
+            let needed = stars.iter().filter(|board::Star(it)| it == pyramid).count();
+            if usize::from(game_board.bank.count(*pyramid)) < needed {
+                return Err(UpdateBankError::NoPyramidsInBank.into());
+            }
         }
 
-        state.game_board.discovered_systems.remove(system_index);
-        Ok(())
+        for board::Star(pyramid) in stars {
+            let old_count = game_board.bank.count(pyramid);
+            utils::update_hashmap_count(
+                game_board.bank.pyramids.entry(pyramid),
+                UpdateOneDelta::RemoveOne,
+                UpdateBankError::BankCountOverflow,
+                UpdateBankError::NoPyramidsInBank,
+            )?;
+            zobrist::update(
+                &mut state.hash,
+                zobrist::bank_key(pyramid, old_count),
+                zobrist::bank_key(pyramid, old_count - 1),
+            );
+        }
+        zobrist::update(&mut state.hash, 0, zobrist::system_hash(&self.star_system));
+        let index = self.index.min(game_board.discovered_systems.len());
+        game_board
+            .discovered_systems
+            .insert(index, self.star_system);
+        Ok(ForgetSystem { star_system: id }.into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetUpHomeworld {
+    pub player: common::Player,
+    pub alpha: board::Star,
+    pub beta: board::Star,
+    pub starship: board::Starship,
+}
+
+#[derive(Error, Debug)]
+pub enum SetUpHomeworldError {
+    #[error("homeworld is already set up")]
+    AlreadySetUp,
+}
+
+impl Apply for SetUpHomeworld {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        utils::check_player(&state.game_board, self.player)?;
+        let homeworld = state.game_board.homeworld_mut(self.player);
+        if !matches!(homeworld.center, board::StarSystemCenter::Empty) || !homeworld.is_empty() {
+            return Err(SetUpHomeworldError::AlreadySetUp.into());
+        }
+        let old_hash = zobrist::system_hash(homeworld);
+        homeworld.center = board::StarSystemCenter::BinaryStar {
+            alpha: self.alpha,
+            beta: self.beta,
+        };
+        homeworld
+            .fleet_mut(self.player)
+            .starships
+            .insert(self.starship, NonZero::new(1).unwrap());
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(homeworld));
+        Ok(TearDownHomeworld {
+            player: self.player,
+        }
+        .into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TearDownHomeworld {
+    pub player: common::Player,
+}
+
+#[derive(Error, Debug)]
+pub enum TearDownHomeworldError {
+    #[error("homeworld is not in the state right after its set up")]
+    NotJustSetUp,
+}
+
+impl Apply for TearDownHomeworld {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        utils::check_player(&state.game_board, self.player)?;
+        let homeworld = state.game_board.homeworld_mut(self.player);
+        let board::StarSystemCenter::BinaryStar { alpha, beta } = homeworld.center else {
+            return Err(TearDownHomeworldError::NotJustSetUp.into());
+        };
+        let fleet = homeworld.fleet(self.player);
+        let starship = match Vec::from_iter(fleet.starships.iter())[..] {
+            [(starship, count)] if count.get() == 1 => *starship,
+            _ => return Err(TearDownHomeworldError::NotJustSetUp.into()),
+        };
+        if homeworld
+            .players()
+            .any(|(player, fleet)| player != self.player && !fleet.is_empty())
+        {
+            return Err(TearDownHomeworldError::NotJustSetUp.into());
+        }
+        let old_hash = zobrist::system_hash(homeworld);
+        homeworld.center = board::StarSystemCenter::Empty;
+        *homeworld.fleet_mut(self.player) = board::Fleet::default();
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(homeworld));
+        Ok(SetUpHomeworld {
+            player: self.player,
+            alpha,
+            beta,
+            starship,
+        }
+        .into())
     }
 }
 
@@ -68,31 +302,36 @@ impl Apply for ForgetSystem {
 mod tests {
     use super::*;
     use crate::public::{board, common, current_turn};
-    use std::num::NonZero;
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: board::SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: board::SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: board::SystemId(3),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         }
@@ -102,7 +341,8 @@ mod tests {
     fn test_discover_system_success() {
         let mut state = create_test_state();
         let op = DiscoverSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
             center_star: board::Star(common::Pyramid {
                 size: common::Size::Small,
                 color: common::Color::Red,
@@ -112,22 +352,27 @@ mod tests {
         let result = op.apply(&mut state);
         assert!(result.is_ok());
         assert_eq!(state.game_board.discovered_systems.len(), 1);
-        assert_eq!(state.game_board.discovered_systems[0].name, "Alpha");
+        assert_eq!(
+            state.game_board.discovered_systems[0].id,
+            board::SystemId(2)
+        );
+        assert_eq!(state.game_board.next_system_id, board::SystemId(3));
     }
 
     #[test]
     fn test_discover_system_duplicate_name() {
         let mut state = create_test_state();
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
             is_homeworld_for: None,
         });
 
         let op = DiscoverSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(3),
+            name: Some("Alpha".to_string()),
             center_star: board::Star(common::Pyramid {
                 size: common::Size::Small,
                 color: common::Color::Red,
@@ -145,15 +390,15 @@ mod tests {
     fn test_forget_system_success() {
         let mut state = create_test_state();
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
             is_homeworld_for: None,
         });
 
         let op = ForgetSystem {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
         };
 
         let result = op.apply(&mut state);
@@ -161,30 +406,71 @@ mod tests {
         assert_eq!(state.game_board.discovered_systems.len(), 0);
     }
 
+    #[test]
+    fn test_forget_system_returns_stars_to_bank() {
+        let mut state = create_test_state();
+        let alpha = common::Pyramid {
+            size: common::Size::Small,
+            color: common::Color::Red,
+        };
+        let beta = common::Pyramid {
+            size: common::Size::Large,
+            color: common::Color::Red,
+        };
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(alpha, NonZero::new(1).unwrap());
+        state.game_board.discovered_systems.push(board::StarSystem {
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
+            center: board::StarSystemCenter::BinaryStar {
+                alpha: board::Star(alpha),
+                beta: board::Star(beta),
+            },
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
+            is_homeworld_for: None,
+        });
+
+        let op = ForgetSystem {
+            star_system: board::SystemId(2),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(result.is_ok());
+        assert!(state.game_board.discovered_systems.is_empty());
+        assert_eq!(state.game_board.bank.count(alpha), 2);
+        assert_eq!(state.game_board.bank.count(beta), 1);
+    }
+
     #[test]
     fn test_forget_system_unknown() {
         let mut state = create_test_state();
         let op = ForgetSystem {
-            star_system_name: "Unknown".to_string(),
+            star_system: board::SystemId(99),
         };
 
         let result = op.apply(&mut state);
-        assert!(matches!(result, Err(OperationError::UnknownStarSystem)));
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownStarSystem { .. })
+        ));
     }
 
     #[test]
     fn test_forget_system_homeworld() {
         let mut state = create_test_state();
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Homeworld".to_string(),
+            id: board::SystemId(2),
+            name: Some("Homeworld".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: board::Fleet::default(),
-            fleet_second: board::Fleet::default(),
-            is_homeworld_for: Some(common::Player::First),
+            fleets: vec![board::Fleet::default(), board::Fleet::default()],
+            is_homeworld_for: Some(common::Player::FIRST),
         });
 
         let op = ForgetSystem {
-            star_system_name: "Homeworld".to_string(),
+            star_system: board::SystemId(2),
         };
 
         let result = op.apply(&mut state);
@@ -209,15 +495,15 @@ mod tests {
         );
 
         state.game_board.discovered_systems.push(board::StarSystem {
-            name: "Alpha".to_string(),
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
             center: board::StarSystemCenter::Empty,
-            fleet_first: fleet,
-            fleet_second: board::Fleet::default(),
+            fleets: vec![fleet, board::Fleet::default()],
             is_homeworld_for: None,
         });
 
         let op = ForgetSystem {
-            star_system_name: "Alpha".to_string(),
+            star_system: board::SystemId(2),
         };
 
         let result = op.apply(&mut state);
@@ -228,4 +514,252 @@ mod tests {
             ))
         ));
     }
+
+    #[test]
+    fn test_set_up_homeworld_success() {
+        let mut state = create_test_state();
+        let starship = board::Starship(common::Pyramid {
+            size: common::Size::Large,
+            color: common::Color::Green,
+        });
+        let op = SetUpHomeworld {
+            player: common::Player::SECOND,
+            alpha: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+            beta: board::Star(common::Pyramid {
+                size: common::Size::Medium,
+                color: common::Color::Blue,
+            }),
+            starship,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(result.is_ok());
+        let homeworld = &state.game_board.homeworlds[1];
+        assert!(matches!(
+            homeworld.center,
+            board::StarSystemCenter::BinaryStar { .. }
+        ));
+        assert!(homeworld.fleets[1].contains(starship));
+        assert!(homeworld.fleets[0].is_empty());
+    }
+
+    #[test]
+    fn test_set_up_homeworld_twice() {
+        let mut state = create_test_state();
+        state.game_board.homeworlds[0].center =
+            board::StarSystemCenter::SingleStar(board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }));
+        let op = SetUpHomeworld {
+            player: common::Player::FIRST,
+            alpha: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+            beta: board::Star(common::Pyramid {
+                size: common::Size::Medium,
+                color: common::Color::Blue,
+            }),
+            starship: board::Starship(common::Pyramid {
+                size: common::Size::Large,
+                color: common::Color::Green,
+            }),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::SetUpHomeworldError(
+                SetUpHomeworldError::AlreadySetUp
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_homeworld_of_unknown_player() {
+        let mut state = create_test_state();
+        let op = SetUpHomeworld {
+            player: common::Player(2),
+            alpha: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+            beta: board::Star(common::Pyramid {
+                size: common::Size::Medium,
+                color: common::Color::Blue,
+            }),
+            starship: board::Starship(common::Pyramid {
+                size: common::Size::Large,
+                color: common::Color::Green,
+            }),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(result, Err(OperationError::UnknownPlayer { .. })));
+        let result = TearDownHomeworld {
+            player: common::Player(2),
+        }
+        .apply(&mut state);
+        assert!(matches!(result, Err(OperationError::UnknownPlayer { .. })));
+    }
+
+    #[test]
+    fn test_discover_system_homeworld_name() {
+        let mut state = create_test_state();
+        let op = DiscoverSystem {
+            id: board::SystemId(2),
+            name: Some("Homeworld1".to_string()),
+            center_star: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::DuplicatedStarSystemName { .. })
+        ));
+        assert!(state.game_board.discovered_systems.is_empty());
+    }
+
+    #[test]
+    fn test_discover_system_duplicate_id() {
+        let mut state = create_test_state();
+        let op = DiscoverSystem {
+            id: board::SystemId::homeworld(common::Player::SECOND),
+            name: None,
+            center_star: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::DuplicatedStarSystemId { .. })
+        ));
+        assert!(state.game_board.discovered_systems.is_empty());
+    }
+
+    #[test]
+    fn test_forgotten_system_id_is_not_reused() {
+        let mut state = create_test_state();
+        let op = DiscoverSystem {
+            id: board::SystemId(5),
+            name: None,
+            center_star: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+        };
+        assert!(op.apply(&mut state).is_ok());
+        let op = ForgetSystem {
+            star_system: board::SystemId(5),
+        };
+        assert!(op.apply(&mut state).is_ok());
+
+        assert_eq!(state.game_board.next_system_id, board::SystemId(6));
+    }
+
+    #[test]
+    fn test_forget_homeworld_slot() {
+        let mut state = create_test_state();
+        let op = ForgetSystem {
+            star_system: board::SystemId(1),
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::ForgetSystemError(
+                ForgetSystemError::CannotForgetHomeworld
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_inverse_of_discover_keeps_star_out_of_bank() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let op = DiscoverSystem {
+            id: board::SystemId(2),
+            name: Some("Alpha".to_string()),
+            center_star: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        let after = state.clone();
+        let redo = inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+        redo.apply(&mut state).unwrap();
+        assert_eq!(state, after);
+    }
+
+    #[test]
+    fn test_inverse_of_forget_restores_system_in_place() {
+        let mut state = create_test_state();
+        let star = common::Pyramid {
+            size: common::Size::Small,
+            color: common::Color::Red,
+        };
+        for (id, name) in [(2, "Alpha"), (3, "Beta")] {
+            state.game_board.discovered_systems.push(board::StarSystem {
+                id: board::SystemId(id),
+                name: Some(name.to_string()),
+                center: board::StarSystemCenter::SingleStar(board::Star(star)),
+                fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                is_homeworld_for: None,
+            });
+        }
+        state.game_board.next_system_id = board::SystemId(4);
+        let before = state.clone();
+        let op = ForgetSystem {
+            star_system: board::SystemId(2),
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        assert_eq!(state.game_board.bank.count(star), 1);
+        let after = state.clone();
+        let redo = inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+        redo.apply(&mut state).unwrap();
+        assert_eq!(state, after);
+    }
+
+    #[test]
+    fn test_inverse_of_set_up_homeworld() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let op = SetUpHomeworld {
+            player: common::Player::FIRST,
+            alpha: board::Star(common::Pyramid {
+                size: common::Size::Small,
+                color: common::Color::Red,
+            }),
+            beta: board::Star(common::Pyramid {
+                size: common::Size::Medium,
+                color: common::Color::Blue,
+            }),
+            starship: board::Starship(common::Pyramid {
+                size: common::Size::Large,
+                color: common::Color::Green,
+            }),
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        let after = state.clone();
+        let redo = inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+        redo.apply(&mut state).unwrap();
+        assert_eq!(state, after);
+    }
 }
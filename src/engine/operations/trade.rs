@@ -0,0 +1,340 @@
+use super::common::UpdateOneDelta;
+use super::fleet::UpdateFleetError;
+use super::utils;
+use super::{Apply, BasicOperation, OperationError, bank::UpdateBankError};
+use crate::engine::zobrist;
+use crate::public::{
+    board::{Starship, SystemId},
+    common::*,
+    current_turn::CurrentTurnState,
+};
+use thiserror::Error;
+
+#[derive(Clone, Debug)]
+pub struct TradeStarship {
+    pub star_system: SystemId,
+    pub player: Player,
+    pub starship: Starship,
+    pub new_color: Color,
+}
+
+#[derive(Error, Debug)]
+pub enum TradeStarshipError {
+    #[error("cannot trade a starship which is not in the fleet")]
+    NoSuchStarship,
+    #[error("cannot trade for {pyramid:?} as there are none in the bank")]
+    NoPyramidInBank { pyramid: Pyramid },
+}
+
+impl Apply for TradeStarship {
+    fn apply(self, state: &mut CurrentTurnState) -> Result<BasicOperation, OperationError> {
+        utils::check_player(&state.game_board, self.player)?;
+        let Starship(old_pyramid) = self.starship;
+        let new_pyramid = Pyramid {
+            color: self.new_color,
+            size: old_pyramid.size,
+        };
+        let new_pyramids_in_bank = state.game_board.bank.count(new_pyramid);
+        let old_pyramids_in_bank = state.game_board.bank.count(old_pyramid);
+        let Some(star_system) = state.game_board.star_system_mut(self.star_system) else {
+            return Err(OperationError::UnknownStarSystem {
+                id: self.star_system,
+            });
+        };
+        let old_hash = zobrist::system_hash(star_system);
+        let fleet = star_system.fleet_mut(self.player);
+
+        // Check everything upfront so that the swap either fully happens or not at all
+        if !fleet.contains(self.starship) {
+            return Err(TradeStarshipError::NoSuchStarship.into());
+        }
+        if new_pyramids_in_bank == 0 {
+            return Err(TradeStarshipError::NoPyramidInBank {
+                pyramid: new_pyramid,
+            }
+            .into());
+        }
+        if old_pyramids_in_bank == u8::MAX {
+            return Err(UpdateBankError::BankCountOverflow.into());
+        }
+        if fleet
+            .starships
+            .get(&Starship(new_pyramid))
+            .is_some_and(|count| count.get() == u8::MAX)
+        {
+            return Err(UpdateFleetError::FleetCountOverflow.into());
+        }
+
+        utils::update_hashmap_count(
+            fleet.starships.entry(self.starship),
+            UpdateOneDelta::RemoveOne,
+            UpdateFleetError::FleetCountOverflow,
+            UpdateFleetError::NoSuchStarships,
+        )?;
+        utils::update_hashmap_count(
+            fleet.starships.entry(Starship(new_pyramid)),
+            UpdateOneDelta::AddOne,
+            UpdateFleetError::FleetCountOverflow,
+            UpdateFleetError::NoSuchStarships,
+        )?;
+        zobrist::update(&mut state.hash, old_hash, zobrist::system_hash(star_system));
+        let bank = &mut state.game_board.bank;
+        utils::update_hashmap_count(
+            bank.pyramids.entry(new_pyramid),
+            UpdateOneDelta::RemoveOne,
+            UpdateBankError::BankCountOverflow,
+            UpdateBankError::NoPyramidsInBank,
+        )?;
+        utils::update_hashmap_count(
+            bank.pyramids.entry(old_pyramid),
+            UpdateOneDelta::AddOne,
+            UpdateBankError::BankCountOverflow,
+            UpdateBankError::NoPyramidsInBank,
+        )?;
+        zobrist::update(
+            &mut state.hash,
+            zobrist::bank_key(new_pyramid, new_pyramids_in_bank)
+                .wrapping_add(zobrist::bank_key(old_pyramid, old_pyramids_in_bank)),
+            zobrist::bank_key(new_pyramid, new_pyramids_in_bank - 1)
+                .wrapping_add(zobrist::bank_key(old_pyramid, old_pyramids_in_bank + 1)),
+        );
+        Ok(TradeStarship {
+            starship: Starship(new_pyramid),
+            new_color: old_pyramid.color,
+            ..self
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public::{board, current_turn};
+    use std::num::NonZero;
+
+    fn create_test_state() -> current_turn::CurrentTurnState {
+        let mut state = current_turn::CurrentTurnState {
+            player: Player::FIRST,
+            current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
+            game_board: board::GameBoard {
+                bank: board::Bank {
+                    pyramids: Default::default(),
+                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(Player::SECOND),
+                    },
+                ],
+                discovered_systems: vec![],
+                next_system_id: SystemId(3),
+            },
+            pending_powers: current_turn::PendingPowers::Nil,
+        };
+        let mut fleet_first = board::Fleet::default();
+        fleet_first.starships.insert(
+            Starship(Pyramid {
+                size: Size::Medium,
+                color: Color::Red,
+            }),
+            NonZero::new(1).unwrap(),
+        );
+        state.game_board.discovered_systems.push(board::StarSystem {
+            id: SystemId(2),
+            name: Some("Alpha".to_string()),
+            center: board::StarSystemCenter::Empty,
+            fleets: vec![fleet_first, board::Fleet::default()],
+            is_homeworld_for: None,
+        });
+        state
+    }
+
+    #[test]
+    fn test_trade_success() {
+        let mut state = create_test_state();
+        let old_pyramid = Pyramid {
+            size: Size::Medium,
+            color: Color::Red,
+        };
+        let new_pyramid = Pyramid {
+            size: Size::Medium,
+            color: Color::Blue,
+        };
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(new_pyramid, NonZero::new(1).unwrap());
+
+        let op = TradeStarship {
+            star_system: SystemId(2),
+            player: Player::FIRST,
+            starship: Starship(old_pyramid),
+            new_color: Color::Blue,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(result.is_ok());
+        let fleet = &state.game_board.discovered_systems[0].fleets[0];
+        assert!(!fleet.contains(Starship(old_pyramid)));
+        assert!(fleet.contains(Starship(new_pyramid)));
+        assert_eq!(state.game_board.bank.count(old_pyramid), 1);
+        assert_eq!(state.game_board.bank.count(new_pyramid), 0);
+    }
+
+    #[test]
+    fn test_trade_no_pyramid_in_bank() {
+        let mut state = create_test_state();
+        let old_pyramid = Pyramid {
+            size: Size::Medium,
+            color: Color::Red,
+        };
+        // Only a pyramid of another size is available
+        state.game_board.bank.pyramids.insert(
+            Pyramid {
+                size: Size::Small,
+                color: Color::Blue,
+            },
+            NonZero::new(1).unwrap(),
+        );
+
+        let op = TradeStarship {
+            star_system: SystemId(2),
+            player: Player::FIRST,
+            starship: Starship(old_pyramid),
+            new_color: Color::Blue,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::TradeStarshipError(
+                TradeStarshipError::NoPyramidInBank { .. }
+            ))
+        ));
+        let fleet = &state.game_board.discovered_systems[0].fleets[0];
+        assert!(fleet.contains(Starship(old_pyramid)));
+        assert_eq!(state.game_board.bank.count(old_pyramid), 0);
+    }
+
+    #[test]
+    fn test_trade_no_such_starship() {
+        let mut state = create_test_state();
+        state.game_board.bank.pyramids.insert(
+            Pyramid {
+                size: Size::Large,
+                color: Color::Blue,
+            },
+            NonZero::new(1).unwrap(),
+        );
+
+        let op = TradeStarship {
+            star_system: SystemId(2),
+            player: Player::FIRST,
+            starship: Starship(Pyramid {
+                size: Size::Large,
+                color: Color::Red,
+            }),
+            new_color: Color::Blue,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::TradeStarshipError(
+                TradeStarshipError::NoSuchStarship
+            ))
+        ));
+        assert_eq!(
+            state.game_board.bank.count(Pyramid {
+                size: Size::Large,
+                color: Color::Blue,
+            }),
+            1
+        );
+    }
+
+    #[test]
+    fn test_unknown_star_system() {
+        let mut state = create_test_state();
+
+        let op = TradeStarship {
+            star_system: SystemId(99),
+            player: Player::FIRST,
+            starship: Starship(Pyramid {
+                size: Size::Medium,
+                color: Color::Red,
+            }),
+            new_color: Color::Blue,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownStarSystem { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_player() {
+        let mut state = create_test_state();
+        let before = state.clone();
+
+        let op = TradeStarship {
+            star_system: SystemId(2),
+            player: Player(2),
+            starship: Starship(Pyramid {
+                size: Size::Medium,
+                color: Color::Red,
+            }),
+            new_color: Color::Blue,
+        };
+
+        let result = op.apply(&mut state);
+        assert!(matches!(
+            result,
+            Err(OperationError::UnknownPlayer { player: Player(2) })
+        ));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_inverse_trades_back() {
+        let mut state = create_test_state();
+        state.game_board.bank.pyramids.insert(
+            Pyramid {
+                size: Size::Medium,
+                color: Color::Blue,
+            },
+            NonZero::new(1).unwrap(),
+        );
+        let before = state.clone();
+        let op = TradeStarship {
+            star_system: SystemId(2),
+            player: Player::FIRST,
+            starship: Starship(Pyramid {
+                size: Size::Medium,
+                color: Color::Red,
+            }),
+            new_color: Color::Blue,
+        };
+
+        let inverse = op.apply(&mut state).unwrap();
+        inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+    }
+}
@@ -0,0 +1,131 @@
+use super::{Apply, BasicOperation, OperationError};
+use crate::public::current_turn::CurrentTurnState;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[error("operation {index} of the batch failed, all previous operations were rolled back")]
+pub struct TransactionError {
+    pub index: usize,
+    #[source]
+    pub error: OperationError,
+}
+
+// Applies either all operations or none of them. Relies on a failed operation leaving
+// the state untouched, so only the operations before it have to be undone.
+// Returns the inverse operations in the order in which they undo the batch.
+// Single operations pass through states like a starship taken out of the bank but not yet
+// placed, so debug builds validate the board after each whole batch instead.
+pub fn apply_all(
+    state: &mut CurrentTurnState,
+    operations: impl IntoIterator<Item = BasicOperation>,
+) -> Result<Vec<BasicOperation>, TransactionError> {
+    let mut inverses = vec![];
+    for (index, operation) in operations.into_iter().enumerate() {
+        match operation.apply(state) {
+            Ok(inverse) => inverses.push(inverse),
+            Err(error) => {
+                rollback(state, inverses);
+                return Err(TransactionError { index, error });
+            }
+        }
+    }
+    debug_assert_eq!(state.game_board.validate(&state.rules), Ok(()));
+    inverses.reverse();
+    Ok(inverses)
+}
+
+fn rollback(state: &mut CurrentTurnState, inverses: Vec<BasicOperation>) {
+    for inverse in inverses.into_iter().rev() {
+        inverse
+            .apply(state)
+            .expect("inverse of an applied operation must apply to the state it produced");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::{
+        bank::{UpdateBank, UpdateBankError},
+        common::UpdateOneDelta,
+        fleet::UpdateFleet,
+        pending_powers::UpdatePendingPowers,
+    };
+    use crate::engine::setup::new_game;
+    use crate::public::{board::*, common::*, current_turn::*};
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        let mut state = new_game(None, None);
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+        state
+    }
+
+    fn build(starship: Pyramid) -> Vec<BasicOperation> {
+        vec![
+            UpdatePendingPowers::Set {
+                power: Power::Build,
+                count: NonZero::new(1).unwrap(),
+            }
+            .into(),
+            UpdatePendingPowers::UseOne.into(),
+            UpdateBank {
+                pyramid: starship,
+                delta: UpdateOneDelta::RemoveOne,
+            }
+            .into(),
+            UpdateFleet {
+                star_system: SystemId::homeworld(Player::FIRST),
+                player: Player::FIRST,
+                starship: Starship(starship),
+                delta: UpdateOneDelta::AddOne,
+            }
+            .into(),
+        ]
+    }
+
+    #[test]
+    fn test_apply_all() {
+        let mut state = create_test_state();
+        let starship = pyramid(Color::Green, Size::Small);
+
+        let inverses = apply_all(&mut state, build(starship)).unwrap();
+        assert_eq!(inverses.len(), 4);
+        assert!(state.game_board.homeworlds[0].fleets[0].contains(Starship(starship)));
+        assert_eq!(state.game_board.bank.count(starship), 2);
+
+        assert!(apply_all(&mut state, inverses).is_ok());
+        assert_eq!(state, create_test_state());
+    }
+
+    #[test]
+    fn test_failed_operation_rolls_back() {
+        let mut state = create_test_state();
+        let starship = pyramid(Color::Green, Size::Small);
+        state.game_board.bank.pyramids.remove(&starship);
+        let before = state.clone();
+
+        let result = apply_all(&mut state, build(starship));
+        assert!(matches!(
+            result,
+            Err(TransactionError {
+                index: 2,
+                error: OperationError::UpdateBankError(UpdateBankError::NoPyramidsInBank),
+            })
+        ));
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_empty_batch() {
+        let mut state = create_test_state();
+
+        let result = apply_all(&mut state, vec![]);
+        assert!(matches!(result, Ok(inverses) if inverses.is_empty()));
+        assert_eq!(state, create_test_state());
+    }
+}
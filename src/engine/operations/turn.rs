@@ -1,8 +1,11 @@
-use super::{Apply, OperationError};
-use crate::public::current_turn::CurrentTurnStatus;
+use super::utils;
+use super::{Apply, BasicOperation, OperationError};
+use crate::engine::zobrist;
+use crate::public::current_turn::{CurrentTurnStatus, PendingPowers};
 use crate::public::*;
 use thiserror::Error;
 
+#[derive(Clone, Debug)]
 pub struct SetCurrentTurnStatus {
     pub new_status: CurrentTurnStatus,
 }
@@ -16,15 +19,144 @@ pub enum SetCurrentTurnStatusError {
 }
 
 impl Apply for SetCurrentTurnStatus {
-    fn apply(self, state: &mut current_turn::CurrentTurnState) -> Result<(), OperationError> {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
         if state.current_turn_status == self.new_status {
             return Err(SetCurrentTurnStatusError::NoChange.into());
         }
         if state.current_turn_status != current_turn::CurrentTurnStatus::MakingActions {
             return Err(SetCurrentTurnStatusError::CanOnlyChangeFromMakingActions.into());
         }
+        let inverse = RestoreTurn::capture(state);
+        let old_hash = zobrist::turn_hash(state);
         state.current_turn_status = self.new_status;
-        Ok(())
+        zobrist::update_turn(state, old_hash);
+        Ok(inverse.into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EndTurn;
+
+#[derive(Error, Debug)]
+pub enum EndTurnError {
+    #[error("cannot end a turn while homeworlds are being set up")]
+    SettingUp,
+}
+
+impl Apply for EndTurn {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        if state.current_turn_status == CurrentTurnStatus::SettingUp {
+            return Err(EndTurnError::SettingUp.into());
+        }
+        let inverse = RestoreTurn::capture(state);
+        let old_hash = zobrist::turn_hash(state);
+        state.player = state.player_on_left(state.player);
+        state.pending_powers = PendingPowers::Nil;
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+        zobrist::update_turn(state, old_hash);
+        Ok(inverse.into())
+    }
+}
+
+// Overwrites everything about the current turn apart from the board
+#[derive(Clone, Debug)]
+pub struct RestoreTurn {
+    pub player: common::Player,
+    pub pending_powers: PendingPowers,
+    pub current_turn_status: CurrentTurnStatus,
+}
+
+impl RestoreTurn {
+    fn capture(state: &current_turn::CurrentTurnState) -> RestoreTurn {
+        RestoreTurn {
+            player: state.player,
+            pending_powers: state.pending_powers,
+            current_turn_status: state.current_turn_status.clone(),
+        }
+    }
+}
+
+impl Apply for RestoreTurn {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        utils::check_player(&state.game_board, self.player)?;
+        let inverse = RestoreTurn::capture(state);
+        let old_hash = zobrist::turn_hash(state);
+        state.player = self.player;
+        state.pending_powers = self.pending_powers;
+        state.current_turn_status = self.current_turn_status;
+        zobrist::update_turn(state, old_hash);
+        Ok(inverse.into())
+    }
+}
+
+// Takes a player out of the game, the turn never passes to them again
+#[derive(Clone, Debug)]
+pub struct EliminatePlayer {
+    pub player: common::Player,
+}
+
+#[derive(Error, Debug)]
+pub enum EliminatePlayerError {
+    #[error("player {player:?} is already eliminated")]
+    AlreadyEliminated { player: common::Player },
+    #[error("player {player:?} is not eliminated")]
+    NotEliminated { player: common::Player },
+}
+
+impl Apply for EliminatePlayer {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        utils::check_player(&state.game_board, self.player)?;
+        if state.is_eliminated(self.player) {
+            return Err(EliminatePlayerError::AlreadyEliminated {
+                player: self.player,
+            }
+            .into());
+        }
+        let old_hash = zobrist::turn_hash(state);
+        state.eliminated.push(self.player);
+        zobrist::update_turn(state, old_hash);
+        Ok(ReinstatePlayer {
+            player: self.player,
+        }
+        .into())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReinstatePlayer {
+    pub player: common::Player,
+}
+
+impl Apply for ReinstatePlayer {
+    fn apply(
+        self,
+        state: &mut current_turn::CurrentTurnState,
+    ) -> Result<BasicOperation, OperationError> {
+        let Some(index) = state.eliminated.iter().position(|it| *it == self.player) else {
+            return Err(EliminatePlayerError::NotEliminated {
+                player: self.player,
+            }
+            .into());
+        };
+        let old_hash = zobrist::turn_hash(state);
+        state.eliminated.remove(index);
+        zobrist::update_turn(state, old_hash);
+        Ok(EliminatePlayer {
+            player: self.player,
+        }
+        .into())
     }
 }
 
@@ -35,27 +167,33 @@ mod tests {
 
     fn create_test_state() -> current_turn::CurrentTurnState {
         current_turn::CurrentTurnState {
-            player: common::Player::First,
+            player: common::Player::FIRST,
             current_turn_status: current_turn::CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
             game_board: board::GameBoard {
                 bank: board::Bank {
                     pyramids: Default::default(),
                 },
-                homeworld_first: board::StarSystem {
-                    name: "Homeworld1".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::First),
-                },
-                homeworld_second: board::StarSystem {
-                    name: "Homeworld2".to_string(),
-                    center: board::StarSystemCenter::Empty,
-                    fleet_first: board::Fleet::default(),
-                    fleet_second: board::Fleet::default(),
-                    is_homeworld_for: Some(common::Player::Second),
-                },
+                homeworlds: vec![
+                    board::StarSystem {
+                        id: board::SystemId(0),
+                        name: Some("Homeworld1".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::FIRST),
+                    },
+                    board::StarSystem {
+                        id: board::SystemId(1),
+                        name: Some("Homeworld2".to_string()),
+                        center: board::StarSystemCenter::Empty,
+                        fleets: vec![board::Fleet::default(), board::Fleet::default()],
+                        is_homeworld_for: Some(common::Player::SECOND),
+                    },
+                ],
                 discovered_systems: vec![],
+                next_system_id: board::SystemId(2),
             },
             pending_powers: current_turn::PendingPowers::Nil,
         }
@@ -129,4 +267,157 @@ mod tests {
             assert!(state.current_turn_status == status);
         }
     }
+
+    #[test]
+    fn test_end_turn() {
+        for status in [
+            CurrentTurnStatus::MakingActions,
+            CurrentTurnStatus::Passing,
+            CurrentTurnStatus::Resigning,
+        ] {
+            let mut state = create_test_state();
+            state.current_turn_status = status;
+            state.pending_powers = current_turn::PendingPowers::Exhausted {
+                power: common::Power::Build,
+                original_count: std::num::NonZero::new(1).unwrap(),
+            };
+
+            let result = EndTurn.apply(&mut state);
+            assert!(result.is_ok());
+            assert!(state.player == common::Player::SECOND);
+            assert!(matches!(
+                state.pending_powers,
+                current_turn::PendingPowers::Nil
+            ));
+            assert!(state.current_turn_status == CurrentTurnStatus::MakingActions);
+        }
+    }
+
+    #[test]
+    fn test_end_turn_during_setup() {
+        let mut state = create_test_state();
+        state.current_turn_status = CurrentTurnStatus::SettingUp;
+
+        let result = EndTurn.apply(&mut state);
+        assert!(matches!(
+            result.err(),
+            Some(OperationError::EndTurnError(EndTurnError::SettingUp))
+        ));
+        assert!(state.player == common::Player::FIRST);
+    }
+
+    #[test]
+    fn test_inverse_restores_turn() {
+        let mut state = create_test_state();
+        state.pending_powers = current_turn::PendingPowers::Exhausted {
+            power: common::Power::Build,
+            original_count: std::num::NonZero::new(1).unwrap(),
+        };
+        let before = state.clone();
+        let inverse_pass = SetCurrentTurnStatus {
+            new_status: CurrentTurnStatus::Passing,
+        }
+        .apply(&mut state)
+        .unwrap();
+        let passed = state.clone();
+        let inverse_end_turn = EndTurn.apply(&mut state).unwrap();
+
+        inverse_end_turn.apply(&mut state).unwrap();
+        assert_eq!(state, passed);
+        inverse_pass.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+    }
+
+    fn create_three_player_state() -> current_turn::CurrentTurnState {
+        let mut state = create_test_state();
+        let third = common::Player(2);
+        for system in state.game_board.star_systems_mut() {
+            system.fleets.push(board::Fleet::default());
+        }
+        state
+            .game_board
+            .homeworlds
+            .push(board::StarSystem::new_homeworld(third, None, 3));
+        state.game_board.next_system_id = board::SystemId(3);
+        state
+    }
+
+    #[test]
+    fn test_end_turn_passes_to_the_left() {
+        let mut state = create_three_player_state();
+
+        for expected in [1, 2, 0] {
+            EndTurn.apply(&mut state).unwrap();
+            assert_eq!(state.player, common::Player(expected));
+        }
+    }
+
+    #[test]
+    fn test_end_turn_skips_eliminated_players() {
+        let mut state = create_three_player_state();
+        EliminatePlayer {
+            player: common::Player::SECOND,
+        }
+        .apply(&mut state)
+        .unwrap();
+
+        EndTurn.apply(&mut state).unwrap();
+        assert_eq!(state.player, common::Player(2));
+        EndTurn.apply(&mut state).unwrap();
+        assert_eq!(state.player, common::Player::FIRST);
+    }
+
+    #[test]
+    fn test_eliminate_player() {
+        let mut state = create_three_player_state();
+        let before = state.clone();
+        let op = EliminatePlayer {
+            player: common::Player(2),
+        };
+
+        let inverse = op.clone().apply(&mut state).unwrap();
+        assert!(state.is_eliminated(common::Player(2)));
+        assert!(matches!(
+            op.apply(&mut state).err(),
+            Some(OperationError::EliminatePlayerError(
+                EliminatePlayerError::AlreadyEliminated { .. }
+            ))
+        ));
+
+        inverse.apply(&mut state).unwrap();
+        assert_eq!(state, before);
+    }
+
+    #[test]
+    fn test_eliminate_unknown_player() {
+        let mut state = create_test_state();
+        let result = EliminatePlayer {
+            player: common::Player(2),
+        }
+        .apply(&mut state);
+
+        assert!(matches!(
+            result.err(),
+            Some(OperationError::UnknownPlayer { .. })
+        ));
+        assert!(state.eliminated.is_empty());
+    }
+
+    #[test]
+    fn test_restore_turn_of_unknown_player() {
+        let mut state = create_test_state();
+        let before = state.clone();
+        let result = RestoreTurn {
+            player: common::Player(2),
+            pending_powers: PendingPowers::Nil,
+            current_turn_status: CurrentTurnStatus::MakingActions,
+        }
+        .apply(&mut state);
+
+        assert!(matches!(
+            result.err(),
+            Some(OperationError::UnknownPlayer { .. })
+        ));
+        assert_eq!(state, before);
+    }
 }
@@ -1,6 +1,16 @@
+use super::OperationError;
 use super::common::UpdateOneDelta;
+use crate::public::{board::GameBoard, common::Player};
 use std::{collections::hash_map::Entry, hash::Hash, num::NonZero};
 
+// Players index fleets and homeworlds, so unknown ones are rejected before any lookup
+pub fn check_player(game_board: &GameBoard, player: Player) -> Result<(), OperationError> {
+    if player.index() >= game_board.player_count() {
+        return Err(OperationError::UnknownPlayer { player });
+    }
+    Ok(())
+}
+
 pub fn update_hashmap_count<K, E>(
     entry: Entry<K, NonZero<u8>>,
     delta: UpdateOneDelta,
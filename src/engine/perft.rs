@@ -0,0 +1,233 @@
+use super::game_over::{check_game_over, end_of_turn_operations};
+use super::move_generator::{can_end_turn, for_each_turn, legal_actions_with_operations};
+use super::operations::transaction::apply_all;
+use crate::public::{actions::Action, current_turn::CurrentTurnState};
+use std::slice::from_ref;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum PerftMode {
+    // Every action is a ply, and so is ending the turn, which has no actions
+    Actions,
+    // Every complete turn is a ply, including the end of the turn
+    Turns,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct DivideEntry {
+    // A single action, or all actions of a turn
+    pub actions: Vec<Action>,
+    pub nodes: u64,
+}
+
+// Number of leaf nodes at the given depth
+pub fn perft(state: &CurrentTurnState, depth: u32, mode: PerftMode) -> u64 {
+    count_nodes(&mut state.clone(), depth, mode)
+}
+
+// Leaf nodes at the given depth, broken down by the first ply
+pub fn divide(state: &CurrentTurnState, depth: u32, mode: PerftMode) -> Vec<DivideEntry> {
+    let mut entries = vec![];
+    if depth == 0 {
+        return entries;
+    }
+    for_each_ply(&mut state.clone(), mode, &mut |state, actions| {
+        entries.push(DivideEntry {
+            actions: actions.to_vec(),
+            nodes: count_nodes(state, depth - 1, mode),
+        })
+    });
+    entries
+}
+
+fn count_nodes(state: &mut CurrentTurnState, depth: u32, mode: PerftMode) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut nodes = 0;
+    for_each_ply(state, mode, &mut |state, _| {
+        nodes += count_nodes(state, depth - 1, mode)
+    });
+    nodes
+}
+
+fn for_each_ply(
+    state: &mut CurrentTurnState,
+    mode: PerftMode,
+    visit: &mut impl FnMut(&mut CurrentTurnState, &[Action]),
+) {
+    match mode {
+        PerftMode::Actions => {
+            for (action, operations) in legal_actions_with_operations(state) {
+                let inverses = apply_all(state, operations).expect("resolved actions must apply");
+                visit(state, from_ref(&action));
+                apply_all(state, inverses).expect("inverse operations must apply");
+            }
+            if can_end_turn(state) && check_game_over(state).is_none() {
+                visit_end_of_turn(state, &[], visit);
+            }
+        }
+        PerftMode::Turns => {
+            // A game that is over stays at the end of the last turn
+            if check_game_over(state).is_some() {
+                return;
+            }
+            for_each_turn(state, &mut |state, actions| {
+                if check_game_over(state).is_some() {
+                    visit(state, actions);
+                    return;
+                }
+                visit_end_of_turn(state, actions, visit);
+            });
+        }
+    }
+}
+
+fn visit_end_of_turn(
+    state: &mut CurrentTurnState,
+    actions: &[Action],
+    visit: &mut impl FnMut(&mut CurrentTurnState, &[Action]),
+) {
+    let operations = end_of_turn_operations(state);
+    let inverses = apply_all(state, operations).expect("a complete turn can be ended");
+    visit(state, actions);
+    apply_all(state, inverses).expect("inverse operations must apply");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::move_generator::{legal_actions, legal_turns};
+    use crate::engine::operations::{Apply, turn::EndTurn};
+    use crate::engine::resolver::resolve_action;
+    use crate::engine::setup::{HomeworldChoice, new_game, set_up_homeworld};
+    use crate::public::{board::*, common::*, current_turn::*};
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        let mut state = new_game(None, None);
+        for (alpha, beta) in [
+            (
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+            ),
+            (
+                pyramid(Color::Yellow, Size::Large),
+                pyramid(Color::Blue, Size::Small),
+            ),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(alpha),
+                beta: Star(beta),
+                starship: Starship(pyramid(Color::Green, Size::Large)),
+            };
+            set_up_homeworld(&mut state, choice).unwrap();
+        }
+        state
+    }
+
+    fn play_turn(state: &CurrentTurnState, actions: &[Action]) -> CurrentTurnState {
+        let mut state = state.clone();
+        for action in actions {
+            let operations = resolve_action(&state, action).unwrap();
+            apply_all(&mut state, operations).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn test_depth_zero() {
+        let state = create_test_state();
+
+        assert_eq!(perft(&state, 0, PerftMode::Actions), 1);
+        assert_eq!(perft(&state, 0, PerftMode::Turns), 1);
+        assert!(divide(&state, 0, PerftMode::Turns).is_empty());
+    }
+
+    #[test]
+    fn test_actions() {
+        let state = create_test_state();
+
+        assert_eq!(
+            perft(&state, 1, PerftMode::Actions),
+            legal_actions(&state).len() as u64
+        );
+        // The build and the trades can be followed by a resignation or the end of the
+        // turn, the pass only by the end of the turn, and the sacrifice loses the game
+        // unless the player resigns. A resignation ends the game.
+        assert_eq!(perft(&state, 2, PerftMode::Actions), 10);
+
+        let entries = divide(&state, 2, PerftMode::Actions);
+        assert_eq!(entries.len(), 7);
+        assert!(entries.contains(&DivideEntry {
+            actions: vec![Action::Pass],
+            nodes: 1,
+        }));
+        assert!(entries.contains(&DivideEntry {
+            actions: vec![Action::Resign],
+            nodes: 0,
+        }));
+        assert!(entries.iter().all(|entry| entry.actions.len() == 1));
+
+        // After ending the turn the next player plays on
+        let after_pass = play_turn(&state, &[Action::Pass]);
+        let entries = divide(&after_pass, 2, PerftMode::Actions);
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].actions.is_empty());
+        let mut next_turn = after_pass.clone();
+        EndTurn.apply(&mut next_turn).unwrap();
+        assert_eq!(entries[0].nodes, legal_actions(&next_turn).len() as u64);
+        assert!(perft(&state, 4, PerftMode::Actions) > 0);
+    }
+
+    #[test]
+    fn test_turns() {
+        let state = create_test_state();
+
+        assert_eq!(perft(&state, 1, PerftMode::Turns), 12);
+
+        // Cross-check against the turns generated after each first turn
+        let entries = divide(&state, 2, PerftMode::Turns);
+        assert_eq!(entries.len(), 12);
+        for entry in &entries {
+            let mut after = play_turn(&state, &entry.actions);
+            let expected = match check_game_over(&after) {
+                Some(_) => 0,
+                None => {
+                    EndTurn.apply(&mut after).unwrap();
+                    legal_turns(&after).len() as u64
+                }
+            };
+            assert_eq!(entry.nodes, expected);
+        }
+        let resignation = entries
+            .iter()
+            .find(|entry| entry.actions == vec![Action::Resign])
+            .unwrap();
+        assert_eq!(resignation.nodes, 0);
+
+        let total: u64 = entries.iter().map(|entry| entry.nodes).sum();
+        assert_eq!(perft(&state, 2, PerftMode::Turns), total);
+    }
+
+    #[test]
+    fn test_state_is_restored() {
+        let state = create_test_state();
+        let mut searched = state.clone();
+
+        count_nodes(&mut searched, 2, PerftMode::Turns);
+        assert_eq!(searched, state);
+        count_nodes(&mut searched, 3, PerftMode::Actions);
+        assert_eq!(searched, state);
+    }
+
+    #[test]
+    fn test_no_plies_after_game_over() {
+        let mut state = create_test_state();
+        state.current_turn_status = CurrentTurnStatus::Resigning;
+
+        assert_eq!(perft(&state, 1, PerftMode::Turns), 0);
+    }
+}
@@ -0,0 +1,1504 @@
+use super::catastrophe;
+use super::connectivity;
+use super::operations::{
+    BasicOperation, bank::UpdateBank, common::UpdateOneDelta, fleet::UpdateFleet,
+    pending_powers::UpdatePendingPowers, systems::DiscoverSystem, trade::TradeStarship,
+    turn::SetCurrentTurnStatus,
+};
+use crate::public::{actions::*, board::*, common::*, current_turn::*};
+use std::num::NonZero;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ResolveActionError {
+    #[error("actions can only be made while the current turn status is MakingActions")]
+    NotMakingActions,
+    #[error("unknown star system {id:?}")]
+    UnknownStarSystem { id: SystemId },
+    #[error("no actions are left in the current turn")]
+    NoActionsLeft,
+    #[error("pending powers only allow {pending:?} actions")]
+    PendingPowerMismatch { pending: Power },
+    #[error("{power:?} power is not available in the star system")]
+    PowerNotAvailable { power: Power },
+    #[error("player has no such starship in the star system")]
+    NoSuchStarship,
+    #[error("opponent has no such starship in the star system")]
+    NoSuchOpponentStarship,
+    #[error("only the player on the left may be attacked, not {player:?}")]
+    CannotAttackPlayer { player: Player },
+    #[error("player has no starship large enough to capture {starship:?}")]
+    NoStarshipLargeEnough { starship: Starship },
+    #[error("cannot move a starship to the star system it is already in")]
+    MoveToSameStarSystem,
+    #[error("star systems {from:?} and {to:?} share a star size and are not connected")]
+    StarSystemsNotConnected { from: SystemId, to: SystemId },
+    #[error("cannot discover {star:?} as the origin star system has a star of the same size")]
+    CannotDiscoverStar { star: Star },
+    #[error("star system with name {name:?} already exists")]
+    DuplicatedStarSystemName { name: String },
+    #[error("player has no {color:?} starship in the star system to build from")]
+    NoStarshipOfColor { color: Color },
+    #[error("no {color:?} pyramids are left in the bank")]
+    NoPyramidsOfColorInBank { color: Color },
+    #[error("there is no {pyramid:?} in the bank")]
+    NoSuchPyramidInBank { pyramid: Pyramid },
+    #[error("cannot trade a starship for the same color")]
+    TradeForSameColor,
+    #[error("cannot trade for {pyramid:?} as there are none left in the bank")]
+    NoPyramidToTradeFor { pyramid: Pyramid },
+    #[error("{color:?} is not overpopulated in the star system")]
+    NotOverpopulated { color: Color },
+    #[error("catastrophes can only be declared once the actions of the turn are used up")]
+    CatastropheBeforeEndOfTurn,
+    #[error("cannot sacrifice a starship after another action in the same turn")]
+    SacrificeAfterAction,
+    #[error("cannot pass after another action in the same turn")]
+    PassAfterAction,
+}
+
+pub fn resolve_action(
+    state: &CurrentTurnState,
+    action: &Action,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    if state.current_turn_status != CurrentTurnStatus::MakingActions {
+        return Err(ResolveActionError::NotMakingActions);
+    }
+    match action {
+        Action::Play {
+            star_system,
+            action,
+        } => {
+            let star_system = find_star_system(&state.game_board, *star_system)?;
+            resolve_action_in_star_system(state, star_system, action)
+        }
+        Action::Pass => {
+            if !matches!(state.pending_powers, PendingPowers::Nil) {
+                return Err(ResolveActionError::PassAfterAction);
+            }
+            Ok(vec![
+                SetCurrentTurnStatus {
+                    new_status: CurrentTurnStatus::Passing,
+                }
+                .into(),
+            ])
+        }
+        Action::Resign => Ok(vec![
+            SetCurrentTurnStatus {
+                new_status: CurrentTurnStatus::Resigning,
+            }
+            .into(),
+        ]),
+    }
+}
+
+fn resolve_action_in_star_system(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    action: &ActionInStarSystem,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    match action {
+        ActionInStarSystem::Build { color } => resolve_build(state, star_system, *color),
+        ActionInStarSystem::Move { starship, target } => {
+            resolve_move(state, star_system, *starship, target)
+        }
+        ActionInStarSystem::Capture { starship } => resolve_capture(state, star_system, *starship),
+        ActionInStarSystem::Trade {
+            starship,
+            new_color,
+        } => resolve_trade(state, star_system, *starship, *new_color),
+        ActionInStarSystem::DeclareCatastrophe { color } => {
+            resolve_declare_catastrophe(state, star_system, *color)
+        }
+        ActionInStarSystem::Sacrifice { starship } => {
+            resolve_sacrifice(state, star_system, *starship)
+        }
+    }
+}
+
+fn resolve_build(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    color: Color,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    let mut operations = use_power(state, star_system, Power::Build)?;
+    if !star_system
+        .fleet(state.player)
+        .starships
+        .keys()
+        .any(|Starship(pyramid)| pyramid.color == color)
+    {
+        return Err(ResolveActionError::NoStarshipOfColor { color });
+    }
+    let pyramid = state
+        .game_board
+        .bank
+        .smallest(color)
+        .ok_or(ResolveActionError::NoPyramidsOfColorInBank { color })?;
+    operations.push(update_bank(pyramid, UpdateOneDelta::RemoveOne));
+    operations.push(update_fleet(
+        star_system.id,
+        state.player,
+        Starship(pyramid),
+        UpdateOneDelta::AddOne,
+    ));
+    Ok(operations)
+}
+
+fn resolve_move(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    starship: Starship,
+    target: &MoveTargetStarSystem,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    let mut operations = use_power(state, star_system, Power::Move)?;
+    if !star_system.fleet(state.player).contains(starship) {
+        return Err(ResolveActionError::NoSuchStarship);
+    }
+    let target_id = match target {
+        MoveTargetStarSystem::Known {
+            star_system: target,
+        } => {
+            let target = find_star_system(&state.game_board, *target)?;
+            if target.id == star_system.id {
+                return Err(ResolveActionError::MoveToSameStarSystem);
+            }
+            if !connectivity::are_connected(&star_system.center, &target.center) {
+                return Err(ResolveActionError::StarSystemsNotConnected {
+                    from: star_system.id,
+                    to: target.id,
+                });
+            }
+            target.id
+        }
+        MoveTargetStarSystem::Discovered { name, star } => {
+            if let Some(name) = name
+                && state.game_board.star_system_by_name(name).is_some()
+            {
+                return Err(ResolveActionError::DuplicatedStarSystemName { name: name.clone() });
+            }
+            if !state.game_board.bank.pyramids.contains_key(&star.0) {
+                return Err(ResolveActionError::NoSuchPyramidInBank { pyramid: star.0 });
+            }
+            if !connectivity::can_discover(&star_system.center, *star) {
+                return Err(ResolveActionError::CannotDiscoverStar { star: *star });
+            }
+            operations.push(update_bank(star.0, UpdateOneDelta::RemoveOne));
+            let id = state.game_board.next_system_id;
+            operations.push(
+                DiscoverSystem {
+                    id,
+                    name: name.clone(),
+                    center_star: *star,
+                }
+                .into(),
+            );
+            id
+        }
+    };
+    operations.push(update_fleet(
+        star_system.id,
+        state.player,
+        starship,
+        UpdateOneDelta::RemoveOne,
+    ));
+    operations.push(update_fleet(
+        target_id,
+        state.player,
+        starship,
+        UpdateOneDelta::AddOne,
+    ));
+    forget_if_abandoned(&mut operations, state, star_system);
+    Ok(operations)
+}
+
+fn resolve_capture(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    starship: Starship,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    let mut operations = use_power(state, star_system, Power::Captute)?;
+    // Attack to the left: only the next player in turn order may be attacked
+    let opponent = state.player_on_left(state.player);
+    if !star_system.fleet(opponent).contains(starship) {
+        return match star_system
+            .players()
+            .find(|(player, fleet)| *player != state.player && fleet.contains(starship))
+        {
+            Some((player, _)) => Err(ResolveActionError::CannotAttackPlayer { player }),
+            None => Err(ResolveActionError::NoSuchOpponentStarship),
+        };
+    }
+    if !star_system
+        .fleet(state.player)
+        .starships
+        .keys()
+        .any(|Starship(pyramid)| pyramid.size >= starship.0.size)
+    {
+        return Err(ResolveActionError::NoStarshipLargeEnough { starship });
+    }
+    operations.push(update_fleet(
+        star_system.id,
+        opponent,
+        starship,
+        UpdateOneDelta::RemoveOne,
+    ));
+    operations.push(update_fleet(
+        star_system.id,
+        state.player,
+        starship,
+        UpdateOneDelta::AddOne,
+    ));
+    Ok(operations)
+}
+
+fn resolve_trade(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    starship: Starship,
+    new_color: Color,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    let mut operations = use_power(state, star_system, Power::Trade)?;
+    if !star_system.fleet(state.player).contains(starship) {
+        return Err(ResolveActionError::NoSuchStarship);
+    }
+    let Starship(old_pyramid) = starship;
+    if old_pyramid.color == new_color {
+        return Err(ResolveActionError::TradeForSameColor);
+    }
+    let new_pyramid = Pyramid {
+        color: new_color,
+        size: old_pyramid.size,
+    };
+    if state.game_board.bank.count(new_pyramid) == 0 {
+        return Err(ResolveActionError::NoPyramidToTradeFor {
+            pyramid: new_pyramid,
+        });
+    }
+    operations.push(
+        TradeStarship {
+            star_system: star_system.id,
+            player: state.player,
+            starship,
+            new_color,
+        }
+        .into(),
+    );
+    Ok(operations)
+}
+
+fn resolve_declare_catastrophe(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    color: Color,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    if !state.rules.catastrophes_any_time
+        && !matches!(state.pending_powers, PendingPowers::Exhausted { .. })
+    {
+        return Err(ResolveActionError::CatastropheBeforeEndOfTurn);
+    }
+    if !catastrophe::is_overpopulated(star_system, color, &state.rules) {
+        return Err(ResolveActionError::NotOverpopulated { color });
+    }
+    Ok(catastrophe::resolve_catastrophe(
+        star_system,
+        color,
+        &state.eliminated,
+    ))
+}
+
+fn resolve_sacrifice(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    starship: Starship,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    if !matches!(state.pending_powers, PendingPowers::Nil) {
+        return Err(ResolveActionError::SacrificeAfterAction);
+    }
+    if !star_system.fleet(state.player).contains(starship) {
+        return Err(ResolveActionError::NoSuchStarship);
+    }
+    let Starship(pyramid) = starship;
+    let mut operations = vec![
+        update_fleet(
+            star_system.id,
+            state.player,
+            starship,
+            UpdateOneDelta::RemoveOne,
+        ),
+        update_bank(pyramid, UpdateOneDelta::AddOne),
+    ];
+    forget_if_abandoned(&mut operations, state, star_system);
+    operations.push(
+        UpdatePendingPowers::Set {
+            power: pyramid.color.power(),
+            count: NonZero::new(pyramid.size.pips()).unwrap(),
+        }
+        .into(),
+    );
+    Ok(operations)
+}
+
+fn use_power(
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+    power: Power,
+) -> Result<Vec<BasicOperation>, ResolveActionError> {
+    let is_available = || {
+        star_system
+            .colors_available_to(state.player)
+            .any(|color| color.power() == power)
+    };
+    match state.pending_powers {
+        PendingPowers::Nil => {
+            if !is_available() {
+                return Err(ResolveActionError::PowerNotAvailable { power });
+            }
+            // A regular action is modelled as a single pending power which is used right away
+            Ok(vec![
+                UpdatePendingPowers::Set {
+                    power,
+                    count: NonZero::new(1).unwrap(),
+                }
+                .into(),
+                UpdatePendingPowers::UseOne.into(),
+            ])
+        }
+        PendingPowers::Pending { power: pending, .. } => {
+            if pending != power {
+                return Err(ResolveActionError::PendingPowerMismatch { pending });
+            }
+            if !state.rules.sacrifice_powers_anywhere && !is_available() {
+                return Err(ResolveActionError::PowerNotAvailable { power });
+            }
+            Ok(vec![UpdatePendingPowers::UseOne.into()])
+        }
+        PendingPowers::Exhausted { .. } => Err(ResolveActionError::NoActionsLeft),
+    }
+}
+
+// To be called when a single starship leaves the star system
+fn forget_if_abandoned(
+    operations: &mut Vec<BasicOperation>,
+    state: &CurrentTurnState,
+    star_system: &StarSystem,
+) {
+    let starships: u32 = star_system
+        .fleets
+        .iter()
+        .flat_map(|fleet| fleet.starships.values())
+        .map(|count| u32::from(count.get()))
+        .sum();
+    if starships == 1 {
+        catastrophe::abandon_system(operations, star_system, &state.eliminated);
+    }
+}
+
+fn find_star_system(
+    game_board: &GameBoard,
+    id: SystemId,
+) -> Result<&StarSystem, ResolveActionError> {
+    game_board
+        .star_system(id)
+        .ok_or(ResolveActionError::UnknownStarSystem { id })
+}
+
+fn update_bank(pyramid: Pyramid, delta: UpdateOneDelta) -> BasicOperation {
+    UpdateBank { pyramid, delta }.into()
+}
+
+fn update_fleet(
+    star_system: SystemId,
+    player: Player,
+    starship: Starship,
+    delta: UpdateOneDelta,
+) -> BasicOperation {
+    UpdateFleet {
+        star_system,
+        player,
+        starship,
+        delta,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::operations::Apply;
+    use crate::engine::setup::{HomeworldChoice, new_game_with_rules, set_up_homeworld};
+
+    const ALPHA: SystemId = SystemId(2);
+    const BETA: SystemId = SystemId(3);
+    const GAMMA: SystemId = SystemId(4);
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn fleet(starships: &[Pyramid]) -> Fleet {
+        let mut fleet = Fleet::default();
+        for pyramid in starships {
+            let count = fleet
+                .starships
+                .get(&Starship(*pyramid))
+                .map_or(1, |count| count.get() + 1);
+            fleet
+                .starships
+                .insert(Starship(*pyramid), NonZero::new(count).unwrap());
+        }
+        fleet
+    }
+
+    // Leaves exactly the pieces which are not on the board in the bank
+    fn fill_bank(state: &mut CurrentTurnState) {
+        let on_board: Vec<Pyramid> = state
+            .game_board
+            .star_systems()
+            .flat_map(|star_system| {
+                let stars = star_system.center.stars().map(|Star(pyramid)| pyramid);
+                let starships = star_system.fleets.iter().flat_map(|fleet| {
+                    fleet
+                        .starships
+                        .iter()
+                        .flat_map(|(Starship(pyramid), count)| {
+                            std::iter::repeat_n(*pyramid, count.get().into())
+                        })
+                });
+                stars.chain(starships).collect::<Vec<_>>()
+            })
+            .collect();
+        let mut bank = Bank::with_pyramids_per_kind(state.rules.pyramids_per_kind);
+        for pyramid in on_board {
+            match NonZero::new(bank.count(pyramid) - 1) {
+                Some(count) => bank.pyramids.insert(pyramid, count),
+                None => bank.pyramids.remove(&pyramid),
+            };
+        }
+        state.game_board.bank = bank;
+    }
+
+    fn star_system(
+        id: SystemId,
+        name: &str,
+        star: Pyramid,
+        first: &[Pyramid],
+        second: &[Pyramid],
+    ) -> StarSystem {
+        StarSystem {
+            id,
+            name: Some(name.to_string()),
+            center: StarSystemCenter::SingleStar(Star(star)),
+            fleets: vec![fleet(first), fleet(second)],
+            is_homeworld_for: None,
+        }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        let mut state = CurrentTurnState {
+            player: Player::FIRST,
+            current_turn_status: CurrentTurnStatus::MakingActions,
+            hash: 0,
+            rules: Default::default(),
+            eliminated: vec![],
+            game_board: GameBoard {
+                bank: Bank::full(),
+                homeworlds: vec![
+                    StarSystem {
+                        id: SystemId::homeworld(Player::FIRST),
+                        name: Some("Homeworld1".to_string()),
+                        center: StarSystemCenter::Empty,
+                        fleets: vec![Fleet::default(), Fleet::default()],
+                        is_homeworld_for: Some(Player::FIRST),
+                    },
+                    StarSystem {
+                        id: SystemId::homeworld(Player::SECOND),
+                        name: Some("Homeworld2".to_string()),
+                        center: StarSystemCenter::Empty,
+                        fleets: vec![Fleet::default(), Fleet::default()],
+                        is_homeworld_for: Some(Player::SECOND),
+                    },
+                ],
+                discovered_systems: vec![
+                    star_system(
+                        ALPHA,
+                        "Alpha",
+                        pyramid(Color::Yellow, Size::Medium),
+                        &[pyramid(Color::Green, Size::Medium)],
+                        &[pyramid(Color::Blue, Size::Small)],
+                    ),
+                    star_system(
+                        BETA,
+                        "Beta",
+                        pyramid(Color::Red, Size::Large),
+                        &[pyramid(Color::Blue, Size::Large)],
+                        &[pyramid(Color::Green, Size::Small)],
+                    ),
+                ],
+                next_system_id: GAMMA,
+            },
+            pending_powers: PendingPowers::Nil,
+        };
+        fill_bank(&mut state);
+        state
+    }
+
+    fn play(star_system: SystemId, action: ActionInStarSystem) -> Action {
+        Action::Play {
+            star_system,
+            action: Box::new(action),
+        }
+    }
+
+    fn resolve_and_apply(
+        state: &mut CurrentTurnState,
+        action: &Action,
+    ) -> Result<(), ResolveActionError> {
+        for operation in resolve_action(state, action)? {
+            operation.apply(state).unwrap();
+        }
+        assert_eq!(state.game_board.validate(&state.rules), Ok(()));
+        Ok(())
+    }
+
+    fn system(state: &CurrentTurnState, id: SystemId) -> &StarSystem {
+        find_star_system(&state.game_board, id).unwrap()
+    }
+
+    #[test]
+    fn test_fixture_is_valid() {
+        let state = create_test_state();
+        assert_eq!(state.game_board.validate(&state.rules), Ok(()));
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Green, Size::Medium)),
+            2
+        );
+    }
+
+    #[test]
+    fn test_build_takes_pyramid_from_bank() {
+        let mut state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        // Green is available through the starship, not through the star
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        let built = pyramid(Color::Green, Size::Small);
+        assert!(system(&state, ALPHA).fleets[0].contains(Starship(built)));
+        assert_eq!(state.game_board.bank.count(built), 1);
+        assert!(matches!(
+            state.pending_powers,
+            PendingPowers::Exhausted {
+                power: Power::Build,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_build_takes_smallest_available() {
+        let mut state = create_test_state();
+        state.game_board.discovered_systems[1].fleets[1] =
+            fleet(&[pyramid(Color::Green, Size::Small); 3]);
+        fill_bank(&mut state);
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        let fleet = &system(&state, ALPHA).fleets[0];
+        assert_eq!(
+            fleet
+                .starships
+                .get(&Starship(pyramid(Color::Green, Size::Medium)))
+                .unwrap()
+                .get(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_build_requires_starship_of_color() {
+        let mut state = create_test_state();
+        state.pending_powers = PendingPowers::Pending {
+            power: Power::Build,
+            count: NonZero::new(1).unwrap(),
+            original_count: NonZero::new(1).unwrap(),
+        };
+        let action = play(ALPHA, ActionInStarSystem::Build { color: Color::Red });
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoStarshipOfColor { color: Color::Red })
+        ));
+    }
+
+    #[test]
+    fn test_build_with_color_exhausted_in_bank() {
+        let mut state = create_test_state();
+        for size in [Size::Small, Size::Medium, Size::Large] {
+            state
+                .game_board
+                .bank
+                .pyramids
+                .remove(&pyramid(Color::Green, size));
+        }
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoPyramidsOfColorInBank {
+                color: Color::Green
+            })
+        ));
+    }
+
+    #[test]
+    fn test_power_not_available() {
+        let state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Capture {
+                starship: Starship(pyramid(Color::Blue, Size::Small)),
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::PowerNotAvailable {
+                power: Power::Captute
+            })
+        ));
+    }
+
+    #[test]
+    fn test_only_one_regular_action_per_turn() {
+        let mut state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        assert!(resolve_and_apply(&mut state, &action).is_ok());
+        let result = resolve_action(&state, &action);
+        assert!(matches!(result, Err(ResolveActionError::NoActionsLeft)));
+    }
+
+    #[test]
+    fn test_pending_power_mismatch() {
+        let mut state = create_test_state();
+        state.pending_powers = PendingPowers::Pending {
+            power: Power::Move,
+            count: NonZero::new(2).unwrap(),
+            original_count: NonZero::new(2).unwrap(),
+        };
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::PendingPowerMismatch {
+                pending: Power::Move
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pending_power_is_used() {
+        let mut state = create_test_state();
+        state.pending_powers = PendingPowers::Pending {
+            power: Power::Trade,
+            count: NonZero::new(2).unwrap(),
+            original_count: NonZero::new(2).unwrap(),
+        };
+        // No blue in Alpha for the first player, but pending powers can be used anywhere
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Trade {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                new_color: Color::Red,
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(matches!(
+            state.pending_powers,
+            PendingPowers::Pending { count, .. } if count.get() == 1
+        ));
+    }
+
+    #[test]
+    fn test_move_to_known_system() {
+        let mut state = create_test_state();
+        let starship = Starship(pyramid(Color::Green, Size::Medium));
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship,
+                target: MoveTargetStarSystem::Known { star_system: BETA },
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(!system(&state, ALPHA).fleets[0].contains(starship));
+        assert!(system(&state, BETA).fleets[0].contains(starship));
+    }
+
+    #[test]
+    fn test_move_to_discovered_system() {
+        let mut state = create_test_state();
+        let starship = Starship(pyramid(Color::Green, Size::Medium));
+        let star = pyramid(Color::Blue, Size::Small);
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship,
+                target: MoveTargetStarSystem::Discovered {
+                    name: Some("Gamma".to_string()),
+                    star: Star(star),
+                },
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(system(&state, GAMMA).fleets[0].contains(starship));
+        assert!(matches!(
+            system(&state, GAMMA).center,
+            StarSystemCenter::SingleStar(Star(it)) if it == star
+        ));
+        assert_eq!(state.game_board.bank.count(star), 1);
+    }
+
+    #[test]
+    fn test_move_out_of_abandoned_system() {
+        let mut state = create_test_state();
+        state.game_board.discovered_systems[0].fleets[1] = Fleet::default();
+        fill_bank(&mut state);
+        let starship = Starship(pyramid(Color::Green, Size::Medium));
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship,
+                target: MoveTargetStarSystem::Known { star_system: BETA },
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(find_star_system(&state.game_board, ALPHA).is_err());
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Yellow, Size::Medium)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_move_unknown_starship() {
+        let state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Blue, Size::Small)),
+                target: MoveTargetStarSystem::Known { star_system: BETA },
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(result, Err(ResolveActionError::NoSuchStarship)));
+    }
+
+    #[test]
+    fn test_move_to_unconnected_system() {
+        let mut state = create_test_state();
+        state.game_board.next_system_id = SystemId(5);
+        state.game_board.discovered_systems.push(star_system(
+            GAMMA,
+            "Gamma",
+            pyramid(Color::Blue, Size::Medium),
+            &[],
+            &[pyramid(Color::Red, Size::Small)],
+        ));
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                target: MoveTargetStarSystem::Known { star_system: GAMMA },
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::StarSystemsNotConnected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_move_to_homeworld_without_stars() {
+        let state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                target: MoveTargetStarSystem::Known {
+                    star_system: SystemId::homeworld(Player::SECOND),
+                },
+            },
+        );
+
+        // Sharing no star size with an empty center doesn't make a system reachable
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::StarSystemsNotConnected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_discover_star_of_origin_size() {
+        let state = create_test_state();
+        let star = Star(pyramid(Color::Blue, Size::Medium));
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                target: MoveTargetStarSystem::Discovered {
+                    name: Some("Gamma".to_string()),
+                    star,
+                },
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::CannotDiscoverStar { star: it }) if it == star
+        ));
+    }
+
+    #[test]
+    fn test_discover_star_missing_from_bank() {
+        let mut state = create_test_state();
+        let star = pyramid(Color::Blue, Size::Large);
+        state.game_board.bank.pyramids.remove(&star);
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                target: MoveTargetStarSystem::Discovered {
+                    name: Some("Gamma".to_string()),
+                    star: Star(star),
+                },
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoSuchPyramidInBank { pyramid }) if pyramid == star
+        ));
+    }
+
+    #[test]
+    fn test_discover_duplicated_name() {
+        let state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                target: MoveTargetStarSystem::Discovered {
+                    name: Some("Beta".to_string()),
+                    star: Star(pyramid(Color::Blue, Size::Small)),
+                },
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::DuplicatedStarSystemName { .. })
+        ));
+    }
+
+    #[test]
+    fn test_capture() {
+        let mut state = create_test_state();
+        let starship = Starship(pyramid(Color::Green, Size::Small));
+        let action = play(BETA, ActionInStarSystem::Capture { starship });
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(!system(&state, BETA).fleets[1].contains(starship));
+        assert!(system(&state, BETA).fleets[0].contains(starship));
+    }
+
+    #[test]
+    fn test_capture_same_size() {
+        let mut state = create_test_state();
+        let starship = Starship(pyramid(Color::Yellow, Size::Large));
+        state.game_board.discovered_systems[1].fleets[1] = fleet(&[starship.0]);
+        fill_bank(&mut state);
+        let action = play(BETA, ActionInStarSystem::Capture { starship });
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(system(&state, BETA).fleets[0].contains(starship));
+        assert!(system(&state, BETA).fleets[1].is_empty());
+    }
+
+    #[test]
+    fn test_capture_larger_starship() {
+        let mut state = create_test_state();
+        let starship = Starship(pyramid(Color::Yellow, Size::Large));
+        state.game_board.discovered_systems[1].fleets[0] =
+            fleet(&[pyramid(Color::Blue, Size::Medium)]);
+        state.game_board.discovered_systems[1].fleets[1] = fleet(&[starship.0]);
+        let action = play(BETA, ActionInStarSystem::Capture { starship });
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoStarshipLargeEnough { starship: it }) if it == starship
+        ));
+    }
+
+    #[test]
+    fn test_capture_own_starship() {
+        let state = create_test_state();
+        let action = play(
+            BETA,
+            ActionInStarSystem::Capture {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoSuchOpponentStarship)
+        ));
+    }
+
+    #[test]
+    fn test_capture_only_to_the_left() {
+        let mut state = new_game_with_rules(vec![None, None, None], Default::default()).unwrap();
+        for (alpha, beta) in [
+            (Color::Red, Color::Blue),
+            (Color::Yellow, Color::Blue),
+            (Color::Yellow, Color::Green),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(pyramid(alpha, Size::Small)),
+                beta: Star(pyramid(beta, Size::Medium)),
+                starship: Starship(pyramid(Color::Green, Size::Large)),
+            };
+            set_up_homeworld(&mut state, choice).unwrap();
+        }
+        let left = Starship(pyramid(Color::Blue, Size::Small));
+        let right = Starship(pyramid(Color::Yellow, Size::Small));
+        let homeworld = state.game_board.homeworld_mut(Player::FIRST);
+        homeworld.fleets[1] = fleet(&[left.0]);
+        homeworld.fleets[2] = fleet(&[right.0]);
+        fill_bank(&mut state);
+        let capture = |starship| {
+            play(
+                SystemId::homeworld(Player::FIRST),
+                ActionInStarSystem::Capture { starship },
+            )
+        };
+
+        assert!(matches!(
+            resolve_action(&state, &capture(right)),
+            Err(ResolveActionError::CannotAttackPlayer { player: Player(2) })
+        ));
+        assert!(resolve_action(&state, &capture(left)).is_ok());
+
+        // Once the player on the left is out, the next one can be attacked
+        state.eliminated.push(Player::SECOND);
+        assert!(resolve_and_apply(&mut state, &capture(right)).is_ok());
+        assert!(system(&state, SystemId::homeworld(Player::FIRST)).fleets[2].is_empty());
+    }
+
+    #[test]
+    fn test_leaving_homeworld_of_eliminated_player() {
+        let mut state = new_game_with_rules(vec![None, None, None], Default::default()).unwrap();
+        for (alpha, beta) in [
+            (Color::Red, Color::Blue),
+            (Color::Yellow, Color::Blue),
+            (Color::Yellow, Color::Green),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(pyramid(alpha, Size::Small)),
+                beta: Star(pyramid(beta, Size::Medium)),
+                starship: Starship(pyramid(Color::Green, Size::Large)),
+            };
+            set_up_homeworld(&mut state, choice).unwrap();
+        }
+        // Only an attacker is left at the homeworld of the eliminated third player
+        let attacker = Starship(pyramid(Color::Blue, Size::Small));
+        let homeworld = state.game_board.homeworld_mut(Player(2));
+        homeworld.fleets[0] = fleet(&[attacker.0]);
+        homeworld.fleets[2] = Fleet::default();
+        state.eliminated.push(Player(2));
+        fill_bank(&mut state);
+        let action = play(
+            SystemId::homeworld(Player(2)),
+            ActionInStarSystem::Move {
+                starship: attacker,
+                target: MoveTargetStarSystem::Discovered {
+                    name: None,
+                    star: Star(pyramid(Color::Red, Size::Large)),
+                },
+            },
+        );
+
+        assert!(resolve_and_apply(&mut state, &action).is_ok());
+        let homeworld = system(&state, SystemId::homeworld(Player(2)));
+        assert_eq!(homeworld.center, StarSystemCenter::Empty);
+        assert!(homeworld.is_empty());
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Green, Size::Medium)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_capture_without_starship_in_system() {
+        let mut state = create_test_state();
+        state.pending_powers = PendingPowers::Pending {
+            power: Power::Captute,
+            count: NonZero::new(1).unwrap(),
+            original_count: NonZero::new(1).unwrap(),
+        };
+        state.game_board.discovered_systems[1].fleets[0] = Fleet::default();
+        let starship = Starship(pyramid(Color::Green, Size::Small));
+        let action = play(BETA, ActionInStarSystem::Capture { starship });
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoStarshipLargeEnough { .. })
+        ));
+    }
+
+    #[test]
+    fn test_capture_unknown_starship() {
+        let state = create_test_state();
+        let action = play(
+            BETA,
+            ActionInStarSystem::Capture {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoSuchOpponentStarship)
+        ));
+    }
+
+    #[test]
+    fn test_trade() {
+        let mut state = create_test_state();
+        let old = pyramid(Color::Blue, Size::Large);
+        let new = pyramid(Color::Yellow, Size::Large);
+        let action = play(
+            BETA,
+            ActionInStarSystem::Trade {
+                starship: Starship(old),
+                new_color: Color::Yellow,
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(!system(&state, BETA).fleets[0].contains(Starship(old)));
+        assert!(system(&state, BETA).fleets[0].contains(Starship(new)));
+        assert_eq!(state.game_board.bank.count(old), 3);
+        assert_eq!(state.game_board.bank.count(new), 2);
+    }
+
+    #[test]
+    fn test_trade_without_pyramid_in_bank() {
+        let mut state = create_test_state();
+        let wanted = pyramid(Color::Green, Size::Large);
+        state.game_board.bank.pyramids.remove(&wanted);
+        let action = play(
+            BETA,
+            ActionInStarSystem::Trade {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+                new_color: Color::Green,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NoPyramidToTradeFor { pyramid }) if pyramid == wanted
+        ));
+    }
+
+    #[test]
+    fn test_trade_requires_blue() {
+        let state = create_test_state();
+        let action = play(
+            ALPHA,
+            ActionInStarSystem::Trade {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+                new_color: Color::Red,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::PowerNotAvailable {
+                power: Power::Trade
+            })
+        ));
+    }
+
+    #[test]
+    fn test_trade_for_same_color() {
+        let state = create_test_state();
+        let action = play(
+            BETA,
+            ActionInStarSystem::Trade {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+                new_color: Color::Blue,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(result, Err(ResolveActionError::TradeForSameColor)));
+    }
+
+    #[test]
+    fn test_declare_catastrophe() {
+        let mut state = create_test_state();
+        state.game_board.discovered_systems[1].fleets[0] = fleet(&[
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Large),
+        ]);
+        state.game_board.discovered_systems[1].fleets[1] = fleet(&[
+            pyramid(Color::Red, Size::Medium),
+            pyramid(Color::Red, Size::Large),
+        ]);
+        fill_bank(&mut state);
+        let action = play(
+            BETA,
+            ActionInStarSystem::DeclareCatastrophe { color: Color::Red },
+        );
+
+        // Catastrophes don't use up the action of the turn
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(matches!(state.pending_powers, PendingPowers::Nil));
+        // With its only star gone, the system is cleaned up and all pieces go back to the bank
+        assert!(find_star_system(&state.game_board, BETA).is_err());
+        let bank = &state.game_board.bank;
+        assert_eq!(bank.count(pyramid(Color::Red, Size::Large)), 3);
+        assert_eq!(bank.count(pyramid(Color::Blue, Size::Large)), 3);
+    }
+
+    #[test]
+    fn test_declare_catastrophe_not_overpopulated() {
+        let state = create_test_state();
+        let action = play(
+            BETA,
+            ActionInStarSystem::DeclareCatastrophe { color: Color::Red },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::NotOverpopulated { color: Color::Red })
+        ));
+    }
+
+    #[test]
+    fn test_sacrifice_grants_pending_powers() {
+        let mut state = create_test_state();
+        let sacrificed = pyramid(Color::Blue, Size::Large);
+        let action = play(
+            BETA,
+            ActionInStarSystem::Sacrifice {
+                starship: Starship(sacrificed),
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(!system(&state, BETA).fleets[0].contains(Starship(sacrificed)));
+        assert_eq!(state.game_board.bank.count(sacrificed), 3);
+        assert!(matches!(
+            state.pending_powers,
+            PendingPowers::Pending {
+                power: Power::Trade,
+                count,
+                ..
+            } if count.get() == 3
+        ));
+    }
+
+    #[test]
+    fn test_sacrifice_last_starship_forgets_system() {
+        let mut state = create_test_state();
+        state.game_board.discovered_systems[1].fleets[1] = Fleet::default();
+        fill_bank(&mut state);
+        let action = play(
+            BETA,
+            ActionInStarSystem::Sacrifice {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+            },
+        );
+
+        let result = resolve_and_apply(&mut state, &action);
+        assert!(result.is_ok());
+        assert!(find_star_system(&state.game_board, BETA).is_err());
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Red, Size::Large)),
+            3
+        );
+        assert!(matches!(
+            state.pending_powers,
+            PendingPowers::Pending { .. }
+        ));
+    }
+
+    #[test]
+    fn test_sacrifice_powers_are_consumed() {
+        let mut state = create_test_state();
+        let alpha = &mut state.game_board.discovered_systems[0];
+        alpha.fleets[0] = fleet(&[
+            pyramid(Color::Yellow, Size::Medium),
+            pyramid(Color::Green, Size::Small),
+        ]);
+        fill_bank(&mut state);
+        let sacrifice = play(
+            ALPHA,
+            ActionInStarSystem::Sacrifice {
+                starship: Starship(pyramid(Color::Yellow, Size::Medium)),
+            },
+        );
+        let move_to_beta = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Small)),
+                target: MoveTargetStarSystem::Known { star_system: BETA },
+            },
+        );
+        let move_back = play(
+            BETA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Small)),
+                target: MoveTargetStarSystem::Known { star_system: ALPHA },
+            },
+        );
+
+        assert!(resolve_and_apply(&mut state, &sacrifice).is_ok());
+        assert!(resolve_and_apply(&mut state, &move_to_beta).is_ok());
+        assert!(resolve_and_apply(&mut state, &move_back).is_ok());
+        assert!(matches!(
+            state.pending_powers,
+            PendingPowers::Exhausted {
+                power: Power::Move,
+                ..
+            }
+        ));
+        assert!(matches!(
+            resolve_action(&state, &move_to_beta),
+            Err(ResolveActionError::NoActionsLeft)
+        ));
+    }
+
+    #[test]
+    fn test_sacrifice_after_action() {
+        let mut state = create_test_state();
+        let build = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+        let sacrifice = play(
+            ALPHA,
+            ActionInStarSystem::Sacrifice {
+                starship: Starship(pyramid(Color::Green, Size::Medium)),
+            },
+        );
+
+        assert!(resolve_and_apply(&mut state, &build).is_ok());
+        let result = resolve_action(&state, &sacrifice);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::SacrificeAfterAction)
+        ));
+    }
+
+    #[test]
+    fn test_unknown_star_system() {
+        let state = create_test_state();
+        let action = play(
+            SystemId(99),
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        let result = resolve_action(&state, &action);
+        assert!(matches!(
+            result,
+            Err(ResolveActionError::UnknownStarSystem { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pass_and_resign() {
+        let mut state = create_test_state();
+        assert!(resolve_and_apply(&mut state, &Action::Pass).is_ok());
+        assert!(state.current_turn_status == CurrentTurnStatus::Passing);
+
+        let mut state = create_test_state();
+        assert!(resolve_and_apply(&mut state, &Action::Resign).is_ok());
+        assert!(state.current_turn_status == CurrentTurnStatus::Resigning);
+    }
+
+    #[test]
+    fn test_pass_after_action() {
+        let mut state = create_test_state();
+        let build = play(
+            ALPHA,
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+
+        assert!(resolve_and_apply(&mut state, &build).is_ok());
+        let result = resolve_action(&state, &Action::Pass);
+        assert!(matches!(result, Err(ResolveActionError::PassAfterAction)));
+        assert!(resolve_action(&state, &Action::Resign).is_ok());
+    }
+
+    #[test]
+    fn test_not_making_actions() {
+        let mut state = create_test_state();
+        state.current_turn_status = CurrentTurnStatus::Passing;
+
+        let result = resolve_action(&state, &Action::Resign);
+        assert!(matches!(result, Err(ResolveActionError::NotMakingActions)));
+    }
+
+    #[test]
+    fn test_catastrophe_only_at_end_of_turn() {
+        let mut state = create_test_state();
+        state.rules.catastrophes_any_time = false;
+        state.game_board.discovered_systems[1].fleets[0] = fleet(&[
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Large),
+        ]);
+        state.game_board.discovered_systems[1].fleets[1] = fleet(&[
+            pyramid(Color::Red, Size::Medium),
+            pyramid(Color::Red, Size::Large),
+        ]);
+        fill_bank(&mut state);
+        let catastrophe = play(
+            BETA,
+            ActionInStarSystem::DeclareCatastrophe { color: Color::Red },
+        );
+        let trade = play(
+            BETA,
+            ActionInStarSystem::Trade {
+                starship: Starship(pyramid(Color::Blue, Size::Large)),
+                new_color: Color::Yellow,
+            },
+        );
+
+        assert!(matches!(
+            resolve_action(&state, &catastrophe),
+            Err(ResolveActionError::CatastropheBeforeEndOfTurn)
+        ));
+        assert!(resolve_and_apply(&mut state, &trade).is_ok());
+        assert!(resolve_and_apply(&mut state, &catastrophe).is_ok());
+    }
+
+    #[test]
+    fn test_sacrifice_powers_need_available_color() {
+        let mut state = create_test_state();
+        state.rules.sacrifice_powers_anywhere = false;
+        let alpha = &mut state.game_board.discovered_systems[0];
+        alpha.fleets[0] = fleet(&[
+            pyramid(Color::Yellow, Size::Medium),
+            pyramid(Color::Green, Size::Small),
+        ]);
+        fill_bank(&mut state);
+        let sacrifice = play(
+            ALPHA,
+            ActionInStarSystem::Sacrifice {
+                starship: Starship(pyramid(Color::Yellow, Size::Medium)),
+            },
+        );
+        let move_to_beta = play(
+            ALPHA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Small)),
+                target: MoveTargetStarSystem::Known { star_system: BETA },
+            },
+        );
+        let move_back = play(
+            BETA,
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Green, Size::Small)),
+                target: MoveTargetStarSystem::Known { star_system: ALPHA },
+            },
+        );
+
+        // Alpha has a yellow star, but there is nothing yellow in Beta
+        assert!(resolve_and_apply(&mut state, &sacrifice).is_ok());
+        assert!(resolve_and_apply(&mut state, &move_to_beta).is_ok());
+        assert!(matches!(
+            resolve_action(&state, &move_back),
+            Err(ResolveActionError::PowerNotAvailable { power: Power::Move })
+        ));
+    }
+}
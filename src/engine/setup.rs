@@ -0,0 +1,297 @@
+use super::operations::{
+    BasicOperation,
+    bank::UpdateBank,
+    common::UpdateOneDelta,
+    systems::SetUpHomeworld,
+    transaction::{TransactionError, apply_all},
+    turn::RestoreTurn,
+};
+use super::zobrist::hash_state;
+use crate::public::{board::*, common::*, current_turn::*, rules::RuleSet};
+use thiserror::Error;
+
+pub fn new_game(
+    homeworld_first_name: Option<String>,
+    homeworld_second_name: Option<String>,
+) -> CurrentTurnState {
+    new_game_with_rules(
+        vec![homeworld_first_name, homeworld_second_name],
+        RuleSet::default(),
+    )
+    .expect("two players are enough for a game")
+}
+
+// One homeworld name for each player, in turn order
+pub fn new_game_with_rules(
+    homeworld_names: Vec<Option<String>>,
+    rules: RuleSet,
+) -> Result<CurrentTurnState, GameBoardError> {
+    let mut game_board = GameBoard::new(homeworld_names)?;
+    game_board.bank = Bank::with_pyramids_per_kind(rules.pyramids_per_kind);
+    let mut state = CurrentTurnState {
+        player: Player::FIRST,
+        game_board,
+        pending_powers: PendingPowers::Nil,
+        current_turn_status: CurrentTurnStatus::SettingUp,
+        hash: 0,
+        rules,
+        eliminated: vec![],
+    };
+    state.hash = hash_state(&state);
+    Ok(state)
+}
+
+pub struct HomeworldChoice {
+    pub alpha: Star,
+    pub beta: Star,
+    pub starship: Starship,
+}
+
+#[derive(Error, Debug)]
+pub enum SetupError {
+    #[error("homeworlds can only be set up before the first turn")]
+    NotSettingUp,
+    #[error("there is no {pyramid:?} left in the bank")]
+    NoSuchPyramidInBank { pyramid: Pyramid },
+    #[error("cannot apply setup operations")]
+    TransactionError(#[from] TransactionError),
+}
+
+// Returns the applied operations
+pub fn set_up_homeworld(
+    state: &mut CurrentTurnState,
+    choice: HomeworldChoice,
+) -> Result<Vec<BasicOperation>, SetupError> {
+    if state.current_turn_status != CurrentTurnStatus::SettingUp {
+        return Err(SetupError::NotSettingUp);
+    }
+    let pyramids = [choice.alpha.0, choice.beta.0, choice.starship.0];
+    for pyramid in pyramids {
+        let needed = pyramids.iter().filter(|it| **it == pyramid).count();
+        if usize::from(state.game_board.bank.count(pyramid)) < needed {
+            return Err(SetupError::NoSuchPyramidInBank { pyramid });
+        }
+    }
+
+    let mut operations: Vec<BasicOperation> = pyramids
+        .into_iter()
+        .map(|pyramid| {
+            UpdateBank {
+                pyramid,
+                delta: UpdateOneDelta::RemoveOne,
+            }
+            .into()
+        })
+        .collect();
+    operations.push(
+        SetUpHomeworld {
+            player: state.player,
+            alpha: choice.alpha,
+            beta: choice.beta,
+            starship: choice.starship,
+        }
+        .into(),
+    );
+    // The first turn starts once the last player is done
+    let next_player = state.player.left(state.game_board.player_count());
+    operations.push(
+        RestoreTurn {
+            player: next_player,
+            pending_powers: PendingPowers::Nil,
+            current_turn_status: match next_player {
+                Player::FIRST => CurrentTurnStatus::MakingActions,
+                _ => CurrentTurnStatus::SettingUp,
+            },
+        }
+        .into(),
+    );
+    apply_all(state, operations.clone())?;
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_state() -> CurrentTurnState {
+        new_game(
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        )
+    }
+
+    fn choice(alpha: Pyramid, beta: Pyramid, starship: Pyramid) -> HomeworldChoice {
+        HomeworldChoice {
+            alpha: Star(alpha),
+            beta: Star(beta),
+            starship: Starship(starship),
+        }
+    }
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    #[test]
+    fn test_full_setup() {
+        let mut state = create_test_state();
+        let first = choice(
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Medium),
+            pyramid(Color::Green, Size::Large),
+        );
+        let second = choice(
+            pyramid(Color::Yellow, Size::Large),
+            pyramid(Color::Blue, Size::Small),
+            pyramid(Color::Green, Size::Large),
+        );
+
+        assert!(set_up_homeworld(&mut state, first).is_ok());
+        assert!(state.player == Player::SECOND);
+        assert!(state.current_turn_status == CurrentTurnStatus::SettingUp);
+
+        assert!(set_up_homeworld(&mut state, second).is_ok());
+        assert!(state.player == Player::FIRST);
+        assert!(state.current_turn_status == CurrentTurnStatus::MakingActions);
+
+        let board = &state.game_board;
+        assert!(matches!(
+            board.homeworlds[0].center,
+            StarSystemCenter::BinaryStar { .. }
+        ));
+        assert!(
+            board.homeworlds[1].fleets[1].contains(Starship(pyramid(Color::Green, Size::Large)))
+        );
+        assert_eq!(board.bank.count(pyramid(Color::Green, Size::Large)), 1);
+        assert_eq!(board.bank.count(pyramid(Color::Blue, Size::Small)), 2);
+    }
+
+    #[test]
+    fn test_pyramid_not_in_bank() {
+        let mut state = create_test_state();
+        let green_large = pyramid(Color::Green, Size::Large);
+        state.game_board.bank.pyramids.remove(&green_large);
+
+        let result = set_up_homeworld(
+            &mut state,
+            choice(
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+                green_large,
+            ),
+        );
+        assert!(matches!(
+            result,
+            Err(SetupError::NoSuchPyramidInBank { pyramid }) if pyramid == green_large
+        ));
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Red, Size::Small)),
+            3
+        );
+    }
+
+    #[test]
+    fn test_same_pyramid_counted_per_use() {
+        let mut state = create_test_state();
+        let red_small = pyramid(Color::Red, Size::Small);
+        state.game_board.bank.pyramids.remove(&red_small);
+        state
+            .game_board
+            .bank
+            .pyramids
+            .insert(red_small, std::num::NonZero::new(2).unwrap());
+
+        let result = set_up_homeworld(&mut state, choice(red_small, red_small, red_small));
+        assert!(matches!(
+            result,
+            Err(SetupError::NoSuchPyramidInBank { .. })
+        ));
+    }
+
+    #[test]
+    fn test_setup_after_game_started() {
+        let mut state = create_test_state();
+        state.current_turn_status = CurrentTurnStatus::MakingActions;
+
+        let result = set_up_homeworld(
+            &mut state,
+            choice(
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+                pyramid(Color::Green, Size::Large),
+            ),
+        );
+        assert!(matches!(result, Err(SetupError::NotSettingUp)));
+    }
+
+    #[test]
+    fn test_setup_with_smaller_bank() {
+        let rules = RuleSet {
+            pyramids_per_kind: std::num::NonZero::new(1).unwrap(),
+            ..RuleSet::default()
+        };
+        let mut state = new_game_with_rules(vec![None, None], rules).unwrap();
+        let green_large = pyramid(Color::Green, Size::Large);
+        assert_eq!(state.game_board.bank.count(green_large), 1);
+
+        let first = choice(
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Medium),
+            green_large,
+        );
+        assert!(set_up_homeworld(&mut state, first).is_ok());
+        assert_eq!(state.game_board.validate(&state.rules), Ok(()));
+
+        // The only green large pyramid is taken already
+        let second = choice(
+            pyramid(Color::Yellow, Size::Large),
+            pyramid(Color::Blue, Size::Small),
+            green_large,
+        );
+        assert!(matches!(
+            set_up_homeworld(&mut state, second),
+            Err(SetupError::NoSuchPyramidInBank { pyramid }) if pyramid == green_large
+        ));
+    }
+
+    #[test]
+    fn test_not_enough_players() {
+        assert!(matches!(
+            new_game_with_rules(vec![None], RuleSet::default()),
+            Err(GameBoardError::NotEnoughPlayers { count: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_three_player_setup() {
+        let mut state = new_game_with_rules(vec![None, None, None], RuleSet::default()).unwrap();
+        for (player, color) in [
+            (Player::FIRST, Color::Red),
+            (Player::SECOND, Color::Yellow),
+            (Player(2), Color::Blue),
+        ] {
+            assert!(state.player == player);
+            assert!(state.current_turn_status == CurrentTurnStatus::SettingUp);
+            let setup = choice(
+                pyramid(color, Size::Small),
+                pyramid(color, Size::Medium),
+                pyramid(Color::Green, Size::Large),
+            );
+            assert!(set_up_homeworld(&mut state, setup).is_ok());
+        }
+
+        assert!(state.player == Player::FIRST);
+        assert!(state.current_turn_status == CurrentTurnStatus::MakingActions);
+        assert_eq!(state.game_board.next_system_id, SystemId(3));
+        assert_eq!(
+            state
+                .game_board
+                .bank
+                .count(pyramid(Color::Green, Size::Large)),
+            0
+        );
+    }
+}
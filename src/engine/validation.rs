@@ -0,0 +1,296 @@
+use crate::public::{board::*, common::*, rules::RuleSet};
+use std::collections::{HashMap, HashSet};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum ValidationError {
+    #[error(
+        "there are {count} pieces of {pyramid:?} on the board and in the bank instead of {expected}"
+    )]
+    PieceCountMismatch {
+        pyramid: Pyramid,
+        count: u32,
+        expected: u8,
+    },
+    #[error("star system {id:?} has no stars")]
+    EmptyCenter { id: SystemId },
+    #[error("star system {id:?} has no starships")]
+    EmptyFleets { id: SystemId },
+    #[error("star system id {id:?} is used more than once")]
+    DuplicatedStarSystemId { id: SystemId },
+    #[error("star system name {name:?} is used more than once")]
+    DuplicatedStarSystemName { name: String },
+    #[error("star system id {id:?} is not below the next id to be handed out")]
+    UnissuedStarSystemId { id: SystemId },
+    #[error("star system {id:?} is in the homeworld slot of {slot:?} but is marked as {marked:?}")]
+    HomeworldSlotMismatch {
+        id: SystemId,
+        slot: Player,
+        marked: Option<Player>,
+    },
+    #[error("discovered star system {id:?} is marked as a homeworld")]
+    DiscoveredHomeworld { id: SystemId },
+    #[error("star system {id:?} has {count} fleets instead of one for each of {expected} players")]
+    FleetCountMismatch {
+        id: SystemId,
+        count: usize,
+        expected: usize,
+    },
+}
+
+impl GameBoard {
+    pub fn validate(&self, rules: &RuleSet) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        self.validate_piece_counts(rules.pyramids_per_kind.get(), &mut errors);
+        self.validate_star_systems(&mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_piece_counts(&self, expected: u8, errors: &mut Vec<ValidationError>) {
+        let mut counts: HashMap<Pyramid, u32> = HashMap::new();
+        for (pyramid, count) in &self.bank.pyramids {
+            *counts.entry(*pyramid).or_default() += u32::from(count.get());
+        }
+        for star_system in self.star_systems() {
+            for Star(pyramid) in star_system.center.stars() {
+                *counts.entry(pyramid).or_default() += 1;
+            }
+            for fleet in &star_system.fleets {
+                for (Starship(pyramid), count) in &fleet.starships {
+                    *counts.entry(*pyramid).or_default() += u32::from(count.get());
+                }
+            }
+        }
+        for color in Color::iter() {
+            for size in Size::iter() {
+                let pyramid = Pyramid { color, size };
+                let count = counts.get(&pyramid).copied().unwrap_or_default();
+                if count != u32::from(expected) {
+                    errors.push(ValidationError::PieceCountMismatch {
+                        pyramid,
+                        count,
+                        expected,
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_star_systems(&self, errors: &mut Vec<ValidationError>) {
+        let mut ids = HashSet::new();
+        let mut names = HashSet::new();
+        for star_system in self.star_systems() {
+            if !ids.insert(star_system.id) {
+                errors.push(ValidationError::DuplicatedStarSystemId { id: star_system.id });
+            }
+            if star_system.id >= self.next_system_id {
+                errors.push(ValidationError::UnissuedStarSystemId { id: star_system.id });
+            }
+            if let Some(name) = &star_system.name
+                && !names.insert(name.as_str())
+            {
+                errors.push(ValidationError::DuplicatedStarSystemName { name: name.clone() });
+            }
+            if star_system.fleets.len() != self.player_count() {
+                errors.push(ValidationError::FleetCountMismatch {
+                    id: star_system.id,
+                    count: star_system.fleets.len(),
+                    expected: self.player_count(),
+                });
+            }
+        }
+
+        for slot in Player::all(self.player_count()) {
+            let homeworld = self.homeworld(slot);
+            if homeworld.id != SystemId::homeworld(slot) || homeworld.is_homeworld_for != Some(slot)
+            {
+                errors.push(ValidationError::HomeworldSlotMismatch {
+                    id: homeworld.id,
+                    slot,
+                    marked: homeworld.is_homeworld_for,
+                });
+            }
+        }
+
+        for star_system in &self.discovered_systems {
+            if star_system.is_homeworld_for.is_some() {
+                errors.push(ValidationError::DiscoveredHomeworld { id: star_system.id });
+            }
+            if matches!(star_system.center, StarSystemCenter::Empty) {
+                errors.push(ValidationError::EmptyCenter { id: star_system.id });
+            }
+            if star_system.is_empty() {
+                errors.push(ValidationError::EmptyFleets { id: star_system.id });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn take_from_bank(game_board: &mut GameBoard, pyramid: Pyramid) {
+        let count = game_board.bank.count(pyramid) - 1;
+        match NonZero::new(count) {
+            Some(count) => game_board.bank.pyramids.insert(pyramid, count),
+            None => game_board.bank.pyramids.remove(&pyramid),
+        };
+    }
+
+    fn create_test_board() -> GameBoard {
+        let mut game_board = GameBoard::new(vec![
+            Some("Homeworld1".to_string()),
+            Some("Homeworld2".to_string()),
+        ])
+        .unwrap();
+        let star = pyramid(Color::Yellow, Size::Small);
+        let starship = pyramid(Color::Green, Size::Large);
+        take_from_bank(&mut game_board, star);
+        take_from_bank(&mut game_board, starship);
+        let mut fleet_first = Fleet::default();
+        fleet_first
+            .starships
+            .insert(Starship(starship), NonZero::new(1).unwrap());
+        game_board.discovered_systems.push(StarSystem {
+            id: SystemId(2),
+            name: Some("Alpha".to_string()),
+            center: StarSystemCenter::SingleStar(Star(star)),
+            fleets: vec![fleet_first, Fleet::default()],
+            is_homeworld_for: None,
+        });
+        game_board.next_system_id = SystemId(3);
+        game_board
+    }
+
+    #[test]
+    fn test_valid_board() {
+        assert_eq!(create_test_board().validate(&RuleSet::default()), Ok(()));
+        assert_eq!(
+            GameBoard::new(vec![
+                Some("Homeworld1".to_string()),
+                Some("Homeworld2".to_string()),
+            ])
+            .unwrap()
+            .validate(&RuleSet::default()),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_missing_piece() {
+        let mut game_board = create_test_board();
+        let lost = pyramid(Color::Red, Size::Medium);
+        take_from_bank(&mut game_board, lost);
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![ValidationError::PieceCountMismatch {
+                pyramid: lost,
+                count: 2,
+                expected: PYRAMIDS_PER_KIND,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_empty_system() {
+        let mut game_board = create_test_board();
+        let star = pyramid(Color::Blue, Size::Large);
+        take_from_bank(&mut game_board, star);
+        game_board.next_system_id = SystemId(4);
+        game_board.discovered_systems.push(StarSystem {
+            id: SystemId(3),
+            name: Some("Beta".to_string()),
+            center: StarSystemCenter::SingleStar(Star(star)),
+            fleets: vec![Fleet::default(), Fleet::default()],
+            is_homeworld_for: None,
+        });
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![ValidationError::EmptyFleets { id: SystemId(3) }])
+        );
+    }
+
+    #[test]
+    fn test_system_without_stars() {
+        let mut game_board = create_test_board();
+        let star = pyramid(Color::Yellow, Size::Small);
+        game_board.discovered_systems[0].center = StarSystemCenter::Empty;
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![
+                ValidationError::PieceCountMismatch {
+                    pyramid: star,
+                    count: 2,
+                    expected: PYRAMIDS_PER_KIND,
+                },
+                ValidationError::EmptyCenter { id: SystemId(2) },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_duplicated_names() {
+        let mut game_board = create_test_board();
+        game_board.discovered_systems[0].name = Some("Homeworld2".to_string());
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![ValidationError::DuplicatedStarSystemName {
+                name: "Homeworld2".to_string()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_star_system_ids() {
+        let mut game_board = create_test_board();
+        game_board.discovered_systems[0].id = SystemId::homeworld(Player::FIRST);
+        game_board.homeworlds[1].id = SystemId(7);
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![
+                ValidationError::UnissuedStarSystemId { id: SystemId(7) },
+                ValidationError::DuplicatedStarSystemId { id: SystemId(0) },
+                ValidationError::HomeworldSlotMismatch {
+                    id: SystemId(7),
+                    slot: Player::SECOND,
+                    marked: Some(Player::SECOND),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_homeworld_flags() {
+        let mut game_board = create_test_board();
+        game_board.homeworlds[1].is_homeworld_for = Some(Player::FIRST);
+        game_board.discovered_systems[0].is_homeworld_for = Some(Player::SECOND);
+
+        assert_eq!(
+            game_board.validate(&RuleSet::default()),
+            Err(vec![
+                ValidationError::HomeworldSlotMismatch {
+                    id: SystemId(1),
+                    slot: Player::SECOND,
+                    marked: Some(Player::FIRST),
+                },
+                ValidationError::DiscoveredHomeworld { id: SystemId(2) },
+            ])
+        );
+    }
+}
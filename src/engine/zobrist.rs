@@ -0,0 +1,252 @@
+use crate::public::{board::*, common::*, current_turn::*};
+
+// Keys are derived from a description of each feature instead of a random table,
+// so they are the same in every run and piece counts are not limited
+const fn splitmix64(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+const BANK: u64 = 1;
+const STAR: u64 = 2;
+const HOMEWORLD: u64 = 5;
+const PLAYER: u64 = 6;
+const PENDING: u64 = 7;
+const EXHAUSTED: u64 = 8;
+const STATUS: u64 = 9;
+const ELIMINATED: u64 = 10;
+// Fleets of each player get their own feature, starting at 16
+const FLEET: u64 = 16;
+
+const fn key(feature: u64, a: u64, b: u64) -> u64 {
+    splitmix64((feature << 48) | (a << 24) | b)
+}
+
+// Pieces of the same kind are keyed by how many of them there are
+fn count_key(feature: u64, pyramid: Pyramid, count: u8) -> u64 {
+    match count {
+        0 => 0,
+        count => key(feature, pyramid.index() as u64, count.into()),
+    }
+}
+
+// The hash is a wrapping sum of the keys instead of the usual xor, so that two
+// systems with the same contents don't cancel each other out
+pub fn update(hash: &mut u64, old: u64, new: u64) {
+    *hash = hash.wrapping_sub(old).wrapping_add(new);
+}
+
+pub fn bank_key(pyramid: Pyramid, count: u8) -> u64 {
+    count_key(BANK, pyramid, count)
+}
+
+// Systems are identified only by their contents, so names, ids and their order on
+// the board don't matter. The sum is mixed so that pieces of different systems
+// can't be told apart from pieces of the same system.
+pub fn system_hash(system: &StarSystem) -> u64 {
+    let mut hash = system
+        .is_homeworld_for
+        .map_or(0, |player| key(HOMEWORLD, player.0.into(), 0));
+    for Star(pyramid) in system.center.stars() {
+        hash = hash.wrapping_add(key(STAR, pyramid.index() as u64, 0));
+    }
+    for (Player(player), fleet) in system.players() {
+        for (Starship(pyramid), count) in &fleet.starships {
+            let feature = FLEET + u64::from(player);
+            hash = hash.wrapping_add(count_key(feature, *pyramid, count.get()));
+        }
+    }
+    splitmix64(hash)
+}
+
+// Everything about the current turn apart from the board
+pub fn turn_hash(state: &CurrentTurnState) -> u64 {
+    let pending_powers = match state.pending_powers {
+        PendingPowers::Nil => 0,
+        PendingPowers::Pending {
+            power,
+            count,
+            original_count,
+        } => key(
+            PENDING,
+            power as u64,
+            (u64::from(count.get()) << 8) | u64::from(original_count.get()),
+        ),
+        PendingPowers::Exhausted {
+            power,
+            original_count,
+        } => key(EXHAUSTED, power as u64, original_count.get().into()),
+    };
+    let eliminated = state.eliminated.iter().fold(0u64, |hash, Player(player)| {
+        hash.wrapping_add(key(ELIMINATED, (*player).into(), 0))
+    });
+    key(PLAYER, state.player.0.into(), 0)
+        .wrapping_add(key(STATUS, state.current_turn_status.clone() as u64, 0))
+        .wrapping_add(pending_powers)
+        .wrapping_add(eliminated)
+}
+
+// Swaps the hash of the turn before a change for the one of the current turn
+pub fn update_turn(state: &mut CurrentTurnState, old_turn_hash: u64) {
+    let new_turn_hash = turn_hash(state);
+    update(&mut state.hash, old_turn_hash, new_turn_hash);
+}
+
+// Computes the hash from scratch, operations keep it up to date incrementally
+pub fn hash_state(state: &CurrentTurnState) -> u64 {
+    let game_board = &state.game_board;
+    let bank = game_board
+        .bank
+        .pyramids
+        .iter()
+        .fold(0u64, |hash, (pyramid, count)| {
+            hash.wrapping_add(bank_key(*pyramid, count.get()))
+        });
+    game_board
+        .star_systems()
+        .fold(bank, |hash, system| hash.wrapping_add(system_hash(system)))
+        .wrapping_add(turn_hash(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::move_generator::for_each_turn;
+    use crate::engine::operations::{Apply, transaction::apply_all, turn::EndTurn};
+    use crate::engine::resolver::resolve_action;
+    use crate::engine::setup::{HomeworldChoice, new_game, set_up_homeworld};
+    use crate::public::actions::*;
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_state() -> CurrentTurnState {
+        let mut state = new_game(None, None);
+        for (alpha, beta) in [
+            (
+                pyramid(Color::Red, Size::Small),
+                pyramid(Color::Blue, Size::Medium),
+            ),
+            (
+                pyramid(Color::Yellow, Size::Large),
+                pyramid(Color::Blue, Size::Small),
+            ),
+        ] {
+            let choice = HomeworldChoice {
+                alpha: Star(alpha),
+                beta: Star(beta),
+                starship: Starship(pyramid(Color::Yellow, Size::Medium)),
+            };
+            set_up_homeworld(&mut state, choice).unwrap();
+        }
+        state
+    }
+
+    fn play(state: &mut CurrentTurnState, star_system: SystemId, action: ActionInStarSystem) {
+        let action = Action::Play {
+            star_system,
+            action: Box::new(action),
+        };
+        let operations = resolve_action(state, &action).unwrap();
+        apply_all(state, operations).unwrap();
+    }
+
+    fn discover(state: &mut CurrentTurnState, name: &str, star: Pyramid) {
+        play(
+            state,
+            SystemId::homeworld(state.player),
+            ActionInStarSystem::Move {
+                starship: Starship(pyramid(Color::Yellow, Size::Medium)),
+                target: MoveTargetStarSystem::Discovered {
+                    name: Some(name.to_string()),
+                    star: Star(star),
+                },
+            },
+        );
+    }
+
+    #[test]
+    fn test_new_game() {
+        let state = new_game(None, None);
+
+        assert_eq!(state.hash, hash_state(&state));
+        assert_eq!(create_test_state().hash, hash_state(&create_test_state()));
+    }
+
+    #[test]
+    fn test_incremental_updates() {
+        let mut state = create_test_state();
+
+        // Two turns deep, including the end of each turn and every undo
+        for_each_turn(&mut state, &mut |state, _| {
+            assert_eq!(state.hash, hash_state(state));
+            let inverse = EndTurn.apply(state).unwrap();
+            for_each_turn(state, &mut |state, _| {
+                assert_eq!(state.hash, hash_state(state));
+            });
+            inverse.apply(state).unwrap();
+        });
+        assert_eq!(state.hash, hash_state(&state));
+        assert_eq!(state, create_test_state());
+    }
+
+    #[test]
+    fn test_independent_of_names_and_order() {
+        let mut state = create_test_state();
+        discover(&mut state, "Alpha", pyramid(Color::Green, Size::Large));
+        EndTurn.apply(&mut state).unwrap();
+        discover(&mut state, "Beta", pyramid(Color::Red, Size::Medium));
+
+        let mut other = state.clone();
+        other.game_board.discovered_systems.reverse();
+        for (index, system) in other.game_board.discovered_systems.iter_mut().enumerate() {
+            system.id = SystemId(10 + index as u32);
+            system.name = None;
+        }
+
+        assert_ne!(state.game_board, other.game_board);
+        assert_eq!(state.hash, hash_state(&other));
+    }
+
+    #[test]
+    fn test_identical_systems_do_not_cancel_out() {
+        let state = create_test_state();
+        let mut other = state.clone();
+        for id in [SystemId(2), SystemId(3)] {
+            let mut fleet_first = Fleet::default();
+            fleet_first.starships.insert(
+                Starship(pyramid(Color::Green, Size::Small)),
+                NonZero::new(1).unwrap(),
+            );
+            other.game_board.discovered_systems.push(StarSystem {
+                id,
+                name: None,
+                center: StarSystemCenter::SingleStar(Star(pyramid(Color::Blue, Size::Large))),
+                fleets: vec![fleet_first, Fleet::default()],
+                is_homeworld_for: None,
+            });
+        }
+
+        assert_ne!(hash_state(&state), hash_state(&other));
+    }
+
+    #[test]
+    fn test_turn_is_part_of_the_hash() {
+        let state = create_test_state();
+
+        let mut other = state.clone();
+        other.player = Player::SECOND;
+        assert_ne!(hash_state(&state), hash_state(&other));
+
+        let mut other = state.clone();
+        other.pending_powers = PendingPowers::Exhausted {
+            power: Power::Build,
+            original_count: NonZero::new(1).unwrap(),
+        };
+        assert_ne!(hash_state(&state), hash_state(&other));
+    }
+}
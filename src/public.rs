@@ -1,5 +1,10 @@
 pub mod actions;
 pub mod board;
 pub mod common;
+pub mod compact_board;
 pub mod current_turn;
+pub mod draw_rules;
+pub mod game_result;
+pub mod notation;
+pub mod rules;
 pub mod turn_state;
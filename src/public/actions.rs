@@ -1,11 +1,13 @@
 use super::board::*;
 use super::common::*;
 
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub enum MoveTargetStarSystem {
-    Known { star_system: StarSystem },
-    Discovered,
+    Known { star_system: SystemId },
+    Discovered { name: Option<String>, star: Star },
 }
 
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub enum ActionInStarSystem {
     Build {
         color: Color,
@@ -29,9 +31,10 @@ pub enum ActionInStarSystem {
     },
 }
 
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub enum Action {
     Play {
-        star_system: StarSystem,
+        star_system: SystemId,
         action: Box<ActionInStarSystem>,
     },
     Pass,
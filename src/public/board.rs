@@ -1,60 +1,211 @@
 use super::common::*;
 use std::collections::HashMap;
 use std::num::NonZero;
+use strum::IntoEnumIterator;
+use thiserror::Error;
 
-#[derive(Copy, Clone)]
+pub const PYRAMIDS_PER_KIND: u8 = 3;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct Star(pub Pyramid);
 
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum BinaryStarId {
     Alpha,
     Beta,
 }
 
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum StarSystemCenter {
     Empty,
     SingleStar(Star),
     BinaryStar { alpha: Star, beta: Star },
 }
 
-#[derive(Eq, Hash, PartialEq, Copy, Clone)]
+impl StarSystemCenter {
+    pub fn stars(&self) -> impl Iterator<Item = Star> {
+        let stars = match *self {
+            StarSystemCenter::Empty => [None, None],
+            StarSystemCenter::SingleStar(star) => [Some(star), None],
+            StarSystemCenter::BinaryStar { alpha, beta } => [Some(alpha), Some(beta)],
+        };
+        stars.into_iter().flatten()
+    }
+}
+
+#[derive(Eq, Hash, PartialEq, Copy, Clone, Debug)]
 pub struct Starship(pub Pyramid);
 
-#[derive(Default)]
+#[derive(Default, Eq, PartialEq, Clone, Debug)]
 pub struct Fleet {
     pub starships: HashMap<Starship, NonZero<u8>>,
 }
 
+impl Fleet {
+    pub fn contains(&self, starship: Starship) -> bool {
+        self.starships.contains_key(&starship)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.starships.is_empty()
+    }
+}
+
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub struct SystemId(pub u32);
+
+impl SystemId {
+    pub fn homeworld(player: Player) -> SystemId {
+        SystemId(player.0.into())
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct StarSystem {
-    pub name: String,
+    pub id: SystemId,
+    pub name: Option<String>,
     pub center: StarSystemCenter,
-    pub fleet_first: Fleet,
-    pub fleet_second: Fleet,
+    // One fleet for each player, in turn order
+    pub fleets: Vec<Fleet>,
     pub is_homeworld_for: Option<Player>,
 }
 
 impl StarSystem {
-    pub fn fleet(&self, player: Player) -> &Fleet {
-        match player {
-            Player::First => &self.fleet_first,
-            Player::Second => &self.fleet_second,
+    pub fn new_homeworld(player: Player, name: Option<String>, player_count: usize) -> StarSystem {
+        StarSystem {
+            id: SystemId::homeworld(player),
+            name,
+            center: StarSystemCenter::Empty,
+            fleets: vec![Fleet::default(); player_count],
+            is_homeworld_for: Some(player),
         }
     }
 
+    pub fn colors_available_to(&self, player: Player) -> impl Iterator<Item = Color> {
+        self.center
+            .stars()
+            .map(|Star(pyramid)| pyramid.color)
+            .chain(
+                self.fleet(player)
+                    .starships
+                    .keys()
+                    .map(|Starship(pyramid)| pyramid.color),
+            )
+    }
+
+    pub fn fleet(&self, player: Player) -> &Fleet {
+        &self.fleets[player.index()]
+    }
+
     pub fn fleet_mut(&mut self, player: Player) -> &mut Fleet {
-        match player {
-            Player::First => &mut self.fleet_first,
-            Player::Second => &mut self.fleet_second,
-        }
+        &mut self.fleets[player.index()]
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = (Player, &Fleet)> {
+        Player::all(self.fleets.len()).zip(&self.fleets)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fleets.iter().all(Fleet::is_empty)
     }
 }
 
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Bank {
     pub pyramids: HashMap<Pyramid, NonZero<u8>>,
 }
 
+impl Bank {
+    pub fn full() -> Bank {
+        Bank::with_pyramids_per_kind(NonZero::new(PYRAMIDS_PER_KIND).unwrap())
+    }
+
+    pub fn with_pyramids_per_kind(count: NonZero<u8>) -> Bank {
+        let pyramids = Color::iter()
+            .flat_map(|color| Size::iter().map(move |size| (Pyramid { color, size }, count)))
+            .collect();
+        Bank { pyramids }
+    }
+
+    pub fn count(&self, pyramid: Pyramid) -> u8 {
+        self.pyramids.get(&pyramid).map_or(0, |count| count.get())
+    }
+
+    pub fn smallest(&self, color: Color) -> Option<Pyramid> {
+        Size::iter()
+            .map(|size| Pyramid { color, size })
+            .find(|pyramid| self.pyramids.contains_key(pyramid))
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum GameBoardError {
+    #[error("a game needs at least two players, not {count}")]
+    NotEnoughPlayers { count: usize },
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct GameBoard {
     pub bank: Bank,
-    pub homeworld_first: StarSystem,
-    pub homeworld_second: StarSystem,
+    // One homeworld for each player, in turn order
+    pub homeworlds: Vec<StarSystem>,
     pub discovered_systems: Vec<StarSystem>,
+    pub next_system_id: SystemId,
+}
+
+impl GameBoard {
+    // One homeworld name for each player, in turn order
+    pub fn new(homeworld_names: Vec<Option<String>>) -> Result<GameBoard, GameBoardError> {
+        let player_count = homeworld_names.len();
+        if player_count < 2 {
+            return Err(GameBoardError::NotEnoughPlayers {
+                count: player_count,
+            });
+        }
+        let homeworlds = Player::all(player_count)
+            .zip(homeworld_names)
+            .map(|(player, name)| StarSystem::new_homeworld(player, name, player_count))
+            .collect();
+        Ok(GameBoard {
+            bank: Bank::full(),
+            homeworlds,
+            discovered_systems: vec![],
+            next_system_id: SystemId(player_count as u32),
+        })
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.homeworlds.len()
+    }
+
+    pub fn star_systems(&self) -> impl Iterator<Item = &StarSystem> {
+        self.homeworlds.iter().chain(self.discovered_systems.iter())
+    }
+
+    pub fn star_systems_mut(&mut self) -> impl Iterator<Item = &mut StarSystem> {
+        self.homeworlds
+            .iter_mut()
+            .chain(self.discovered_systems.iter_mut())
+    }
+
+    pub fn star_system(&self, id: SystemId) -> Option<&StarSystem> {
+        self.star_systems().find(|it| it.id == id)
+    }
+
+    pub fn star_system_mut(&mut self, id: SystemId) -> Option<&mut StarSystem> {
+        self.star_systems_mut().find(|it| it.id == id)
+    }
+
+    pub fn star_system_by_name(&self, name: &str) -> Option<&StarSystem> {
+        self.star_systems()
+            .find(|it| it.name.as_deref() == Some(name))
+    }
+
+    pub fn homeworld(&self, player: Player) -> &StarSystem {
+        &self.homeworlds[player.index()]
+    }
+
+    pub fn homeworld_mut(&mut self, player: Player) -> &mut StarSystem {
+        &mut self.homeworlds[player.index()]
+    }
 }
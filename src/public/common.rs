@@ -1,4 +1,20 @@
-#[derive(Eq, Hash, PartialEq, Copy, Clone)]
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use thiserror::Error;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum ParsePieceError {
+    #[error("invalid color {0:?}, expected one of g, y, r and b")]
+    Color(String),
+    #[error("invalid size {0:?}, expected one of 1, 2 and 3")]
+    Size(String),
+    #[error("invalid pyramid {0:?}, expected a color and a size like g3")]
+    Pyramid(String),
+}
+
+#[derive(Eq, Hash, PartialEq, Copy, Clone, Debug, EnumIter)]
 pub enum Color {
     Green,
     Yellow,
@@ -6,13 +22,73 @@ pub enum Color {
     Blue,
 }
 
-#[derive(Eq, Hash, PartialEq, Copy, Clone)]
+impl Color {
+    pub fn power(self) -> Power {
+        match self {
+            Color::Green => Power::Build,
+            Color::Yellow => Power::Move,
+            Color::Red => Power::Captute,
+            Color::Blue => Power::Trade,
+        }
+    }
+}
+
+// Colors and sizes are written as in the Super Duper Games notation
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Color::Green => "g",
+            Color::Yellow => "y",
+            Color::Red => "r",
+            Color::Blue => "b",
+        })
+    }
+}
+
+impl FromStr for Color {
+    type Err = ParsePieceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::iter()
+            .find(|color| color.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| ParsePieceError::Color(s.to_string()))
+    }
+}
+
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Copy, Clone, Debug, EnumIter)]
 pub enum Size {
     Small,
     Medium,
     Large,
 }
 
+impl Size {
+    pub fn pips(self) -> u8 {
+        match self {
+            Size::Small => 1,
+            Size::Medium => 2,
+            Size::Large => 3,
+        }
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pips())
+    }
+}
+
+impl FromStr for Size {
+    type Err = ParsePieceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Size::iter()
+            .find(|size| size.to_string() == s)
+            .ok_or_else(|| ParsePieceError::Size(s.to_string()))
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Power {
     Build,
     Move,
@@ -20,13 +96,71 @@ pub enum Power {
     Trade,
 }
 
-#[derive(Eq, Hash, PartialEq, Copy, Clone)]
+#[derive(Eq, Hash, PartialEq, Copy, Clone, Debug)]
 pub struct Pyramid {
     pub color: Color,
     pub size: Size,
 }
 
-pub enum Player {
-    First,
-    Second,
+// Number of distinct pyramids, one for each combination of color and size
+pub const PYRAMID_KINDS: usize = 12;
+
+impl Pyramid {
+    // Dense index in 0..PYRAMID_KINDS, for tables with an entry per kind of pyramid
+    pub fn index(self) -> usize {
+        self.color as usize * 3 + self.size as usize
+    }
+
+    pub fn from_index(index: usize) -> Pyramid {
+        let color = Color::iter()
+            .nth(index / 3)
+            .expect("pyramid index out of range");
+        let size = Size::iter()
+            .nth(index % 3)
+            .expect("pyramid index out of range");
+        Pyramid { color, size }
+    }
+}
+
+impl fmt::Display for Pyramid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.color, self.size)
+    }
+}
+
+impl FromStr for Pyramid {
+    type Err = ParsePieceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParsePieceError::Pyramid(s.to_string());
+        if !s.is_ascii() || s.len() != 2 {
+            return Err(invalid());
+        }
+        let (color, size) = s.split_at(1);
+        Ok(Pyramid {
+            color: color.parse()?,
+            size: size.parse()?,
+        })
+    }
+}
+
+// Players are numbered in turn order, the turn passes to the player on the left
+#[derive(Eq, Hash, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub struct Player(pub u8);
+
+impl Player {
+    pub const FIRST: Player = Player(0);
+    pub const SECOND: Player = Player(1);
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn all(player_count: usize) -> impl Iterator<Item = Player> {
+        (0..player_count as u8).map(Player)
+    }
+
+    pub fn left(self, player_count: usize) -> Player {
+        Player(((self.index() + 1) % player_count) as u8)
+    }
 }
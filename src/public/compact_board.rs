@@ -0,0 +1,467 @@
+use super::board::*;
+use super::common::*;
+use std::collections::HashMap;
+use std::num::NonZero;
+use thiserror::Error;
+
+// Each discovered system needs a star and a starship, so the 30 pieces left next to two
+// set up homeworlds in the standard set are enough for 15 of them. This is not a bound on
+// every position: a destroyed homeworld gives its stars back to the bank, and a RuleSet
+// can have more than 3 pyramids per kind. from_game_board returns TooManySystems then.
+pub const MAX_DISCOVERED_SYSTEMS: usize = 15;
+pub const MAX_PLAYERS: usize = 4;
+
+// Number of pieces of every kind, indexed by Pyramid::index
+#[derive(Default, Eq, PartialEq, Copy, Clone, Debug)]
+pub struct PyramidCounts(pub [u8; PYRAMID_KINDS]);
+
+impl PyramidCounts {
+    pub fn count(&self, pyramid: Pyramid) -> u8 {
+        self.0[pyramid.index()]
+    }
+
+    pub fn count_mut(&mut self, pyramid: Pyramid) -> &mut u8 {
+        &mut self.0[pyramid.index()]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&count| count == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pyramid, NonZero<u8>)> {
+        self.0
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, count)| Some((Pyramid::from_index(index), NonZero::new(count)?)))
+    }
+
+    fn from_map<K: Copy>(map: &HashMap<K, NonZero<u8>>, pyramid: impl Fn(K) -> Pyramid) -> Self {
+        let mut counts = PyramidCounts::default();
+        for (key, count) in map {
+            *counts.count_mut(pyramid(*key)) = count.get();
+        }
+        counts
+    }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct CompactStarSystem {
+    pub id: SystemId,
+    pub center: StarSystemCenter,
+    // Fleets of players who are not in the game stay empty
+    pub fleets: [PyramidCounts; MAX_PLAYERS],
+}
+
+impl CompactStarSystem {
+    const EMPTY: CompactStarSystem = CompactStarSystem {
+        id: SystemId(0),
+        center: StarSystemCenter::Empty,
+        fleets: [PyramidCounts([0; PYRAMID_KINDS]); MAX_PLAYERS],
+    };
+
+    pub fn fleet(&self, player: Player) -> &PyramidCounts {
+        &self.fleets[player.index()]
+    }
+
+    pub fn fleet_mut(&mut self, player: Player) -> &mut PyramidCounts {
+        &mut self.fleets[player.index()]
+    }
+
+    fn from_star_system(star_system: &StarSystem) -> Self {
+        let mut fleets = [PyramidCounts::default(); MAX_PLAYERS];
+        for (slot, fleet) in fleets.iter_mut().zip(&star_system.fleets) {
+            *slot = PyramidCounts::from_map(&fleet.starships, |Starship(it)| it);
+        }
+        CompactStarSystem {
+            id: star_system.id,
+            center: star_system.center,
+            fleets,
+        }
+    }
+
+    fn to_star_system(
+        self,
+        names: &SystemNames,
+        player_count: usize,
+        is_homeworld_for: Option<Player>,
+    ) -> StarSystem {
+        let fleet = |counts: PyramidCounts| Fleet {
+            starships: counts
+                .iter()
+                .map(|(pyramid, count)| (Starship(pyramid), count))
+                .collect(),
+        };
+        StarSystem {
+            id: self.id,
+            name: names.0.get(&self.id).cloned(),
+            center: self.center,
+            fleets: self.fleets[..player_count]
+                .iter()
+                .copied()
+                .map(fleet)
+                .collect(),
+            is_homeworld_for,
+        }
+    }
+}
+
+// Names don't affect the game, so they are kept apart from the compact board
+#[derive(Default, Eq, PartialEq, Clone, Debug)]
+pub struct SystemNames(pub HashMap<SystemId, String>);
+
+impl SystemNames {
+    pub fn of(game_board: &GameBoard) -> SystemNames {
+        SystemNames(
+            game_board
+                .star_systems()
+                .filter_map(|system| Some((system.id, system.name.clone()?)))
+                .collect(),
+        )
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum CompactBoardError {
+    #[error("only {MAX_DISCOVERED_SYSTEMS} discovered systems fit into a compact board")]
+    TooManySystems,
+    #[error("only games of up to {MAX_PLAYERS} players fit into a compact board")]
+    TooManyPlayers,
+    #[error("homeworld {id:?} is not in its slot")]
+    HomeworldSlotMismatch { id: SystemId },
+}
+
+// Allocation-free copy of a GameBoard for search
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct CompactBoard {
+    pub bank: PyramidCounts,
+    homeworlds: [CompactStarSystem; MAX_PLAYERS],
+    player_count: u8,
+    discovered_systems: [CompactStarSystem; MAX_DISCOVERED_SYSTEMS],
+    discovered_count: u8,
+    pub next_system_id: SystemId,
+}
+
+impl CompactBoard {
+    pub fn from_game_board(game_board: &GameBoard) -> Result<CompactBoard, CompactBoardError> {
+        if game_board.player_count() > MAX_PLAYERS {
+            return Err(CompactBoardError::TooManyPlayers);
+        }
+        let mut homeworlds = [CompactStarSystem::EMPTY; MAX_PLAYERS];
+        for (player, slot) in Player::all(game_board.player_count()).zip(&mut homeworlds) {
+            let homeworld = game_board.homeworld(player);
+            if homeworld.is_homeworld_for != Some(player) {
+                return Err(CompactBoardError::HomeworldSlotMismatch { id: homeworld.id });
+            }
+            *slot = CompactStarSystem::from_star_system(homeworld);
+        }
+        if game_board.discovered_systems.len() > MAX_DISCOVERED_SYSTEMS {
+            return Err(CompactBoardError::TooManySystems);
+        }
+        let mut discovered_systems = [CompactStarSystem::EMPTY; MAX_DISCOVERED_SYSTEMS];
+        for (slot, system) in discovered_systems
+            .iter_mut()
+            .zip(&game_board.discovered_systems)
+        {
+            *slot = CompactStarSystem::from_star_system(system);
+        }
+        Ok(CompactBoard {
+            bank: PyramidCounts::from_map(&game_board.bank.pyramids, |it| it),
+            homeworlds,
+            player_count: game_board.player_count() as u8,
+            discovered_systems,
+            discovered_count: game_board.discovered_systems.len() as u8,
+            next_system_id: game_board.next_system_id,
+        })
+    }
+
+    pub fn to_game_board(self, names: &SystemNames) -> GameBoard {
+        let player_count = self.player_count();
+        GameBoard {
+            bank: Bank {
+                pyramids: self.bank.iter().collect(),
+            },
+            homeworlds: Player::all(player_count)
+                .zip(self.homeworlds())
+                .map(|(player, system)| system.to_star_system(names, player_count, Some(player)))
+                .collect(),
+            discovered_systems: self
+                .discovered_systems()
+                .iter()
+                .map(|system| system.to_star_system(names, player_count, None))
+                .collect(),
+            next_system_id: self.next_system_id,
+        }
+    }
+
+    pub fn player_count(&self) -> usize {
+        usize::from(self.player_count)
+    }
+
+    pub fn homeworlds(&self) -> &[CompactStarSystem] {
+        &self.homeworlds[..self.player_count()]
+    }
+
+    pub fn homeworld(&self, player: Player) -> &CompactStarSystem {
+        &self.homeworlds()[player.index()]
+    }
+
+    pub fn homeworld_mut(&mut self, player: Player) -> &mut CompactStarSystem {
+        let player_count = self.player_count();
+        &mut self.homeworlds[..player_count][player.index()]
+    }
+
+    pub fn discovered_systems(&self) -> &[CompactStarSystem] {
+        &self.discovered_systems[..usize::from(self.discovered_count)]
+    }
+
+    pub fn discovered_systems_mut(&mut self) -> &mut [CompactStarSystem] {
+        &mut self.discovered_systems[..usize::from(self.discovered_count)]
+    }
+
+    pub fn star_systems(&self) -> impl Iterator<Item = &CompactStarSystem> {
+        self.homeworlds().iter().chain(self.discovered_systems())
+    }
+
+    pub fn star_system(&self, id: SystemId) -> Option<&CompactStarSystem> {
+        self.star_systems().find(|it| it.id == id)
+    }
+
+    pub fn push_discovered_system(
+        &mut self,
+        system: CompactStarSystem,
+    ) -> Result<(), CompactBoardError> {
+        let slot = self
+            .discovered_systems
+            .get_mut(usize::from(self.discovered_count))
+            .ok_or(CompactBoardError::TooManySystems)?;
+        *slot = system;
+        self.discovered_count += 1;
+        Ok(())
+    }
+
+    // Keeps the order of the remaining systems, like Vec::remove
+    pub fn remove_discovered_system(&mut self, index: usize) -> CompactStarSystem {
+        let count = usize::from(self.discovered_count);
+        assert!(index < count, "discovered system index out of range");
+        let system = self.discovered_systems[index];
+        self.discovered_systems.copy_within(index + 1..count, index);
+        self.discovered_systems[count - 1] = CompactStarSystem::EMPTY;
+        self.discovered_count -= 1;
+        system
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn fleet(starships: &[(Pyramid, u8)]) -> Fleet {
+        Fleet {
+            starships: starships
+                .iter()
+                .map(|&(pyramid, count)| (Starship(pyramid), NonZero::new(count).unwrap()))
+                .collect(),
+        }
+    }
+
+    fn create_test_board() -> GameBoard {
+        let mut game_board = GameBoard::new(vec![Some("Sol".to_string()), None]).unwrap();
+        game_board.homeworlds[0].center = StarSystemCenter::BinaryStar {
+            alpha: Star(pyramid(Color::Red, Size::Small)),
+            beta: Star(pyramid(Color::Blue, Size::Medium)),
+        };
+        game_board.homeworlds[0].fleets[0] = fleet(&[(pyramid(Color::Green, Size::Large), 1)]);
+        game_board.homeworlds[1].center =
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Yellow, Size::Large)));
+        game_board.homeworlds[1].fleets[1] = fleet(&[
+            (pyramid(Color::Green, Size::Large), 1),
+            (pyramid(Color::Red, Size::Small), 2),
+        ]);
+        for (id, name, star) in [
+            (
+                SystemId(4),
+                Some("Vega"),
+                pyramid(Color::Green, Size::Medium),
+            ),
+            (SystemId(2), None, pyramid(Color::Blue, Size::Large)),
+        ] {
+            game_board.discovered_systems.push(StarSystem {
+                id,
+                name: name.map(str::to_string),
+                center: StarSystemCenter::SingleStar(Star(star)),
+                fleets: vec![
+                    fleet(&[(pyramid(Color::Yellow, Size::Small), 1)]),
+                    fleet(&[(pyramid(Color::Blue, Size::Small), 1)]),
+                ],
+                is_homeworld_for: None,
+            });
+        }
+        game_board.next_system_id = SystemId(5);
+        game_board
+    }
+
+    #[test]
+    fn test_pyramid_index() {
+        for index in 0..PYRAMID_KINDS {
+            assert_eq!(Pyramid::from_index(index).index(), index);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game_board = create_test_board();
+
+        let compact = CompactBoard::from_game_board(&game_board).unwrap();
+        assert_eq!(compact.discovered_systems().len(), 2);
+        assert_eq!(
+            compact
+                .homeworld(Player::SECOND)
+                .fleet(Player::SECOND)
+                .count(pyramid(Color::Red, Size::Small)),
+            2
+        );
+        assert_eq!(
+            compact.to_game_board(&SystemNames::of(&game_board)),
+            game_board
+        );
+    }
+
+    #[test]
+    fn test_without_names() {
+        let game_board = create_test_board();
+
+        let converted = CompactBoard::from_game_board(&game_board)
+            .unwrap()
+            .to_game_board(&SystemNames::default());
+        assert!(converted.star_systems().all(|system| system.name.is_none()));
+        assert_eq!(
+            converted.star_system(SystemId(4)).unwrap().center,
+            game_board.star_system(SystemId(4)).unwrap().center
+        );
+    }
+
+    #[test]
+    fn test_too_many_systems() {
+        let mut game_board = create_test_board();
+        let system = game_board.discovered_systems[1].clone();
+        game_board.discovered_systems = (0..=MAX_DISCOVERED_SYSTEMS as u32)
+            .map(|id| StarSystem {
+                id: SystemId(id + 2),
+                ..system.clone()
+            })
+            .collect();
+
+        assert!(matches!(
+            CompactBoard::from_game_board(&game_board),
+            Err(CompactBoardError::TooManySystems)
+        ));
+    }
+
+    #[test]
+    fn test_destroyed_homeworld_over_the_limit() {
+        let mut game_board = GameBoard::new(vec![None, None]).unwrap();
+        let homeworld = [
+            pyramid(Color::Red, Size::Small),
+            pyramid(Color::Blue, Size::Medium),
+            pyramid(Color::Green, Size::Large),
+        ];
+        game_board.homeworlds[1].center = StarSystemCenter::BinaryStar {
+            alpha: Star(homeworld[0]),
+            beta: Star(homeworld[1]),
+        };
+        game_board.homeworlds[1].fleets[1] = fleet(&[(homeworld[2], 1)]);
+        // The first homeworld is gone, which leaves 33 pieces for discovered systems
+        let mut pieces: Vec<Pyramid> = (0..PYRAMID_KINDS)
+            .flat_map(|index| [Pyramid::from_index(index); PYRAMIDS_PER_KIND as usize])
+            .collect();
+        for pyramid in homeworld {
+            let index = pieces.iter().position(|it| *it == pyramid).unwrap();
+            pieces.remove(index);
+        }
+        let mut pairs = pieces.chunks_exact(2);
+        game_board.discovered_systems = (&mut pairs)
+            .zip(2..)
+            .map(|(pair, id)| StarSystem {
+                id: SystemId(id),
+                name: None,
+                center: StarSystemCenter::SingleStar(Star(pair[0])),
+                fleets: vec![fleet(&[(pair[1], 1)]), Fleet::default()],
+                is_homeworld_for: None,
+            })
+            .collect();
+        game_board.next_system_id = SystemId(2 + game_board.discovered_systems.len() as u32);
+        game_board.bank = Bank {
+            pyramids: pairs
+                .remainder()
+                .iter()
+                .map(|pyramid| (*pyramid, NonZero::new(1).unwrap()))
+                .collect(),
+        };
+
+        assert_eq!(
+            game_board.discovered_systems.len(),
+            MAX_DISCOVERED_SYSTEMS + 1
+        );
+        assert!(matches!(
+            CompactBoard::from_game_board(&game_board),
+            Err(CompactBoardError::TooManySystems)
+        ));
+    }
+
+    #[test]
+    fn test_four_players() {
+        let mut game_board = GameBoard::new(vec![None; MAX_PLAYERS]).unwrap();
+        let fourth = Player(3);
+        game_board.homeworld_mut(fourth).center =
+            StarSystemCenter::SingleStar(Star(pyramid(Color::Green, Size::Small)));
+        *game_board.homeworld_mut(Player::FIRST).fleet_mut(fourth) =
+            fleet(&[(pyramid(Color::Red, Size::Large), 1)]);
+
+        let compact = CompactBoard::from_game_board(&game_board).unwrap();
+        assert_eq!(compact.homeworlds().len(), MAX_PLAYERS);
+        assert_eq!(compact.to_game_board(&SystemNames::default()), game_board);
+    }
+
+    #[test]
+    fn test_too_many_players() {
+        let game_board = GameBoard::new(vec![None; MAX_PLAYERS + 1]).unwrap();
+
+        assert!(matches!(
+            CompactBoard::from_game_board(&game_board),
+            Err(CompactBoardError::TooManyPlayers)
+        ));
+    }
+
+    #[test]
+    fn test_remove_and_push_systems() {
+        let game_board = create_test_board();
+        let mut compact = CompactBoard::from_game_board(&game_board).unwrap();
+
+        let removed = compact.remove_discovered_system(0);
+        assert_eq!(removed.id, SystemId(4));
+        assert_eq!(compact.discovered_systems().len(), 1);
+        assert!(compact.star_system(SystemId(4)).is_none());
+
+        compact.push_discovered_system(removed).unwrap();
+        let ids: Vec<SystemId> = compact
+            .discovered_systems()
+            .iter()
+            .map(|it| it.id)
+            .collect();
+        assert_eq!(ids, [SystemId(2), SystemId(4)]);
+    }
+
+    #[test]
+    fn test_is_copy() {
+        let compact = CompactBoard::from_game_board(&create_test_board()).unwrap();
+        let mut copy = compact;
+
+        *copy.bank.count_mut(pyramid(Color::Green, Size::Small)) = 0;
+        assert_ne!(copy, compact);
+        assert!(size_of::<CompactBoard>() <= 1280);
+    }
+}
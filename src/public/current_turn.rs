@@ -1,8 +1,10 @@
 use super::board::*;
 use super::common::*;
+use super::rules::RuleSet;
 use std::num::NonZero;
 use strum_macros::EnumIter;
 
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum PendingPowers {
     Nil,
     Pending {
@@ -16,16 +18,45 @@ pub enum PendingPowers {
     },
 }
 
-#[derive(Eq, PartialEq, EnumIter, Clone)]
+#[derive(Eq, PartialEq, EnumIter, Clone, Debug)]
 pub enum CurrentTurnStatus {
+    SettingUp,
     MakingActions,
     Passing,
     Resigning,
 }
 
+#[derive(Eq, PartialEq, Clone, Debug)]
 pub struct CurrentTurnState {
     pub player: Player,
     pub game_board: GameBoard,
     pub pending_powers: PendingPowers,
     pub current_turn_status: CurrentTurnStatus,
+    // Zobrist hash of the position, kept up to date by every operation
+    pub hash: u64,
+    pub rules: RuleSet,
+    // Players who lost while the game went on, in the order they were eliminated
+    pub eliminated: Vec<Player>,
+}
+
+impl CurrentTurnState {
+    pub fn is_eliminated(&self, player: Player) -> bool {
+        self.eliminated.contains(&player)
+    }
+
+    pub fn active_players(&self) -> impl Iterator<Item = Player> {
+        Player::all(self.game_board.player_count()).filter(|player| !self.is_eliminated(*player))
+    }
+
+    // The next player in turn order who is still in the game. It is also the only
+    // player who may be attacked, once the players on the left are eliminated the
+    // attacks go on to the next one.
+    pub fn player_on_left(&self, player: Player) -> Player {
+        let player_count = self.game_board.player_count();
+        let mut left = player.left(player_count);
+        while left != player && self.is_eliminated(left) {
+            left = left.left(player_count);
+        }
+        left
+    }
 }
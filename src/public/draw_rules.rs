@@ -0,0 +1,19 @@
+use std::num::NonZero;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct RepetitionRule {
+    // How often the same position has to occur at the start of a turn
+    pub count: NonZero<u32>,
+    // Otherwise any player may claim the draw during the turn
+    pub automatic: bool,
+}
+
+// None of the draw rules apply by default
+#[derive(Default, Eq, PartialEq, Copy, Clone, Debug)]
+pub struct DrawRules {
+    pub repetition: Option<RepetitionRule>,
+    // The game is drawn once this many turns were played
+    pub turn_limit: Option<NonZero<u32>>,
+    // The game is drawn once all players still in the game passed in a row
+    pub mutual_pass: bool,
+}
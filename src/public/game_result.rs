@@ -0,0 +1,22 @@
+use super::common::*;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum LossReason {
+    Resigned,
+    HomeworldStarsDestroyed,
+    HomeworldStarshipsLost,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum DrawReason {
+    AllHomeworldsLost,
+    Repetition,
+    TurnLimit,
+    MutualPass,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum GameResult {
+    Win { winner: Player, reason: LossReason },
+    Draw { reason: DrawReason },
+}
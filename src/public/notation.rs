@@ -0,0 +1,450 @@
+use super::actions::*;
+use super::board::*;
+use super::common::*;
+use thiserror::Error;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum NotationError {
+    #[error("empty action")]
+    Empty,
+    #[error("unknown action {0:?}")]
+    UnknownAction(String),
+    #[error("{action} expects {expected} arguments but got {count}")]
+    WrongArgumentCount {
+        action: String,
+        expected: usize,
+        count: usize,
+    },
+    #[error("invalid piece")]
+    InvalidPiece(#[from] ParsePieceError),
+    #[error("unknown star system {name:?}")]
+    UnknownStarSystemName { name: String },
+    #[error("unknown star system {id:?}")]
+    UnknownStarSystem { id: SystemId },
+    #[error("star system {id:?} has no name to write it down with")]
+    UnnamedStarSystem { id: SystemId },
+    #[error("the star system discovered with {star:?} has no name to write it down with")]
+    UnnamedDiscovery { star: Star },
+    #[error("{pyramid} is not the smallest pyramid of its color in the bank")]
+    NotSmallestInBank { pyramid: Pyramid },
+    #[error("there is no {color} pyramid left in the bank to build")]
+    NothingToBuild { color: Color },
+    #[error("{starship} can only be traded for a pyramid of the same size, not {new}")]
+    TradeSizeMismatch { starship: Pyramid, new: Pyramid },
+}
+
+// Reads a single action in the notation of Super Duper Games, like `move y2 Alpha Beta`.
+// Star systems are looked up by name on the board the action is played on.
+pub fn parse_action(game_board: &GameBoard, text: &str) -> Result<Action, NotationError> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let (keyword, arguments) = words.split_first().ok_or(NotationError::Empty)?;
+    let keyword = keyword.to_ascii_lowercase();
+    let expect = |expected| {
+        if arguments.len() == expected {
+            Ok(())
+        } else {
+            Err(NotationError::WrongArgumentCount {
+                action: keyword.clone(),
+                expected,
+                count: arguments.len(),
+            })
+        }
+    };
+    let system = |name: &str| {
+        game_board
+            .star_system_by_name(name)
+            .map(|it| it.id)
+            .ok_or_else(|| NotationError::UnknownStarSystemName {
+                name: name.to_string(),
+            })
+    };
+    let play = |star_system, action| Action::Play {
+        star_system,
+        action: Box::new(action),
+    };
+
+    let action = match keyword.as_str() {
+        "pass" => {
+            expect(0)?;
+            Action::Pass
+        }
+        "resign" => {
+            expect(0)?;
+            Action::Resign
+        }
+        "build" => {
+            expect(2)?;
+            let pyramid: Pyramid = arguments[0].parse()?;
+            // The notation names the built pyramid, which is always the smallest one
+            if game_board.bank.smallest(pyramid.color) != Some(pyramid) {
+                return Err(NotationError::NotSmallestInBank { pyramid });
+            }
+            let color = pyramid.color;
+            play(system(arguments[1])?, ActionInStarSystem::Build { color })
+        }
+        "move" => {
+            expect(3)?;
+            let starship = Starship(arguments[0].parse()?);
+            let target = MoveTargetStarSystem::Known {
+                star_system: system(arguments[2])?,
+            };
+            play(
+                system(arguments[1])?,
+                ActionInStarSystem::Move { starship, target },
+            )
+        }
+        "discover" => {
+            expect(4)?;
+            let starship = Starship(arguments[0].parse()?);
+            let target = MoveTargetStarSystem::Discovered {
+                name: Some(arguments[3].to_string()),
+                star: Star(arguments[2].parse()?),
+            };
+            play(
+                system(arguments[1])?,
+                ActionInStarSystem::Move { starship, target },
+            )
+        }
+        "trade" => {
+            expect(3)?;
+            let starship: Pyramid = arguments[0].parse()?;
+            let new: Pyramid = arguments[1].parse()?;
+            if new.size != starship.size {
+                return Err(NotationError::TradeSizeMismatch { starship, new });
+            }
+            play(
+                system(arguments[2])?,
+                ActionInStarSystem::Trade {
+                    starship: Starship(starship),
+                    new_color: new.color,
+                },
+            )
+        }
+        "attack" => {
+            expect(2)?;
+            let starship = Starship(arguments[0].parse()?);
+            play(
+                system(arguments[1])?,
+                ActionInStarSystem::Capture { starship },
+            )
+        }
+        "sacrifice" => {
+            expect(2)?;
+            let starship = Starship(arguments[0].parse()?);
+            play(
+                system(arguments[1])?,
+                ActionInStarSystem::Sacrifice { starship },
+            )
+        }
+        "catastrophe" => {
+            expect(2)?;
+            let color = arguments[1].parse()?;
+            play(
+                system(arguments[0])?,
+                ActionInStarSystem::DeclareCatastrophe { color },
+            )
+        }
+        _ => return Err(NotationError::UnknownAction(keyword)),
+    };
+    Ok(action)
+}
+
+// Writes an action in the notation of Super Duper Games. Like parsing, it needs the
+// board from before the action, for the names of the systems and the built pyramid.
+pub fn format_action(game_board: &GameBoard, action: &Action) -> Result<String, NotationError> {
+    let (star_system, action) = match action {
+        Action::Pass => return Ok("pass".to_string()),
+        Action::Resign => return Ok("resign".to_string()),
+        Action::Play {
+            star_system,
+            action,
+        } => (*star_system, action.as_ref()),
+    };
+    let name = |id| {
+        let system = game_board
+            .star_system(id)
+            .ok_or(NotationError::UnknownStarSystem { id })?;
+        system
+            .name
+            .as_deref()
+            .ok_or(NotationError::UnnamedStarSystem { id })
+    };
+    let origin = name(star_system)?;
+
+    let text = match action {
+        ActionInStarSystem::Build { color } => {
+            let pyramid = game_board
+                .bank
+                .smallest(*color)
+                .ok_or(NotationError::NothingToBuild { color: *color })?;
+            format!("build {pyramid} {origin}")
+        }
+        ActionInStarSystem::Move {
+            starship: Starship(pyramid),
+            target: MoveTargetStarSystem::Known { star_system },
+        } => format!("move {pyramid} {origin} {}", name(*star_system)?),
+        ActionInStarSystem::Move {
+            starship: Starship(pyramid),
+            target: MoveTargetStarSystem::Discovered { name, star },
+        } => {
+            let target = name
+                .as_deref()
+                .ok_or(NotationError::UnnamedDiscovery { star: *star })?;
+            format!("discover {pyramid} {origin} {} {target}", star.0)
+        }
+        ActionInStarSystem::Trade {
+            starship: Starship(pyramid),
+            new_color,
+        } => {
+            let new = Pyramid {
+                color: *new_color,
+                size: pyramid.size,
+            };
+            format!("trade {pyramid} {new} {origin}")
+        }
+        ActionInStarSystem::Capture {
+            starship: Starship(pyramid),
+        } => format!("attack {pyramid} {origin}"),
+        ActionInStarSystem::Sacrifice {
+            starship: Starship(pyramid),
+        } => format!("sacrifice {pyramid} {origin}"),
+        ActionInStarSystem::DeclareCatastrophe { color } => {
+            format!("catastrophe {origin} {color}")
+        }
+    };
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZero;
+
+    fn pyramid(color: Color, size: Size) -> Pyramid {
+        Pyramid { color, size }
+    }
+
+    fn create_test_board() -> GameBoard {
+        let mut game_board = GameBoard::new(vec![Some("Home".to_string()), None]).unwrap();
+        for (id, name, star) in [
+            (SystemId(2), "Alpha", pyramid(Color::Red, Size::Large)),
+            (SystemId(3), "Beta", pyramid(Color::Blue, Size::Medium)),
+        ] {
+            let mut fleet = Fleet::default();
+            fleet.starships.insert(
+                Starship(pyramid(Color::Yellow, Size::Medium)),
+                NonZero::new(1).unwrap(),
+            );
+            game_board.discovered_systems.push(StarSystem {
+                id,
+                name: Some(name.to_string()),
+                center: StarSystemCenter::SingleStar(Star(star)),
+                fleets: vec![fleet, Fleet::default()],
+                is_homeworld_for: None,
+            });
+        }
+        game_board.next_system_id = SystemId(4);
+        game_board
+    }
+
+    fn play(star_system: SystemId, action: ActionInStarSystem) -> Action {
+        Action::Play {
+            star_system,
+            action: Box::new(action),
+        }
+    }
+
+    #[test]
+    fn test_pieces() {
+        let blue_large = pyramid(Color::Blue, Size::Large);
+        assert_eq!(blue_large.to_string(), "b3");
+        assert_eq!("b3".parse(), Ok(blue_large));
+        assert_eq!("G1".parse(), Ok(pyramid(Color::Green, Size::Small)));
+        assert_eq!("y".parse(), Ok(Color::Yellow));
+        assert_eq!("2".parse(), Ok(Size::Medium));
+
+        assert_eq!(
+            "x1".parse::<Pyramid>(),
+            Err(ParsePieceError::Color("x".to_string()))
+        );
+        assert_eq!(
+            "r4".parse::<Pyramid>(),
+            Err(ParsePieceError::Size("4".to_string()))
+        );
+        assert_eq!(
+            "r".parse::<Pyramid>(),
+            Err(ParsePieceError::Pyramid("r".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game_board = create_test_board();
+        let home = SystemId::homeworld(Player::FIRST);
+        let alpha = SystemId(2);
+        let beta = SystemId(3);
+        let yellow_medium = Starship(pyramid(Color::Yellow, Size::Medium));
+        let cases = [
+            (
+                "build g1 Alpha",
+                play(
+                    alpha,
+                    ActionInStarSystem::Build {
+                        color: Color::Green,
+                    },
+                ),
+            ),
+            (
+                "move y2 Alpha Beta",
+                play(
+                    alpha,
+                    ActionInStarSystem::Move {
+                        starship: yellow_medium,
+                        target: MoveTargetStarSystem::Known { star_system: beta },
+                    },
+                ),
+            ),
+            (
+                "discover g1 Home b2 Gamma",
+                play(
+                    home,
+                    ActionInStarSystem::Move {
+                        starship: Starship(pyramid(Color::Green, Size::Small)),
+                        target: MoveTargetStarSystem::Discovered {
+                            name: Some("Gamma".to_string()),
+                            star: Star(pyramid(Color::Blue, Size::Medium)),
+                        },
+                    },
+                ),
+            ),
+            (
+                "trade r1 b1 Home",
+                play(
+                    home,
+                    ActionInStarSystem::Trade {
+                        starship: Starship(pyramid(Color::Red, Size::Small)),
+                        new_color: Color::Blue,
+                    },
+                ),
+            ),
+            (
+                "attack y2 Alpha",
+                play(
+                    alpha,
+                    ActionInStarSystem::Capture {
+                        starship: yellow_medium,
+                    },
+                ),
+            ),
+            (
+                "sacrifice r3 Home",
+                play(
+                    home,
+                    ActionInStarSystem::Sacrifice {
+                        starship: Starship(pyramid(Color::Red, Size::Large)),
+                    },
+                ),
+            ),
+            (
+                "catastrophe Home b",
+                play(
+                    home,
+                    ActionInStarSystem::DeclareCatastrophe { color: Color::Blue },
+                ),
+            ),
+            ("pass", Action::Pass),
+            ("resign", Action::Resign),
+        ];
+
+        for (text, action) in cases {
+            assert_eq!(parse_action(&game_board, text), Ok(action.clone()));
+            assert_eq!(format_action(&game_board, &action), Ok(text.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_extra_whitespace_and_case() {
+        let game_board = create_test_board();
+
+        assert_eq!(
+            parse_action(&game_board, "  Sacrifice  R3\tHome "),
+            parse_action(&game_board, "sacrifice r3 Home")
+        );
+    }
+
+    #[test]
+    fn test_invalid_actions() {
+        let game_board = create_test_board();
+
+        assert_eq!(parse_action(&game_board, " "), Err(NotationError::Empty));
+        assert_eq!(
+            parse_action(&game_board, "fly y2 Alpha Beta"),
+            Err(NotationError::UnknownAction("fly".to_string()))
+        );
+        assert_eq!(
+            parse_action(&game_board, "move y2 Alpha"),
+            Err(NotationError::WrongArgumentCount {
+                action: "move".to_string(),
+                expected: 3,
+                count: 2,
+            })
+        );
+        assert_eq!(
+            parse_action(&game_board, "move y2 Alpha Delta"),
+            Err(NotationError::UnknownStarSystemName {
+                name: "Delta".to_string()
+            })
+        );
+        assert_eq!(
+            parse_action(&game_board, "trade r1 b2 Home"),
+            Err(NotationError::TradeSizeMismatch {
+                starship: pyramid(Color::Red, Size::Small),
+                new: pyramid(Color::Blue, Size::Medium),
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_takes_smallest() {
+        let mut game_board = create_test_board();
+        let green_small = pyramid(Color::Green, Size::Small);
+        game_board.bank.pyramids.remove(&green_small);
+
+        assert_eq!(
+            parse_action(&game_board, "build g1 Home"),
+            Err(NotationError::NotSmallestInBank {
+                pyramid: green_small
+            })
+        );
+        let build = play(
+            SystemId::homeworld(Player::FIRST),
+            ActionInStarSystem::Build {
+                color: Color::Green,
+            },
+        );
+        assert_eq!(
+            parse_action(&game_board, "build g2 Home"),
+            Ok(build.clone())
+        );
+        assert_eq!(
+            format_action(&game_board, &build),
+            Ok("build g2 Home".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unnamed_systems() {
+        let game_board = create_test_board();
+        let action = play(
+            SystemId::homeworld(Player::SECOND),
+            ActionInStarSystem::DeclareCatastrophe { color: Color::Red },
+        );
+
+        assert_eq!(
+            format_action(&game_board, &action),
+            Err(NotationError::UnnamedStarSystem {
+                id: SystemId::homeworld(Player::SECOND)
+            })
+        );
+    }
+}
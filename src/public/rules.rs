@@ -0,0 +1,28 @@
+use super::board::PYRAMIDS_PER_KIND;
+use std::num::NonZero;
+
+pub const OVERPOPULATION_THRESHOLD: u8 = 4;
+
+// House rules, the defaults are the standard rules of the game
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct RuleSet {
+    // Pieces of every color and size in the bank before the homeworlds are set up
+    pub pyramids_per_kind: NonZero<u8>,
+    // Pieces of a color in a star system which make it overpopulated
+    pub overpopulation_threshold: NonZero<u8>,
+    // Otherwise catastrophes can only be declared after the actions of the turn are used up
+    pub catastrophes_any_time: bool,
+    // Otherwise the powers of a sacrifice need the color to be available, like regular actions
+    pub sacrifice_powers_anywhere: bool,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            pyramids_per_kind: NonZero::new(PYRAMIDS_PER_KIND).unwrap(),
+            overpopulation_threshold: NonZero::new(OVERPOPULATION_THRESHOLD).unwrap(),
+            catastrophes_any_time: true,
+            sacrifice_powers_anywhere: true,
+        }
+    }
+}
@@ -1 +1,9 @@
+use super::actions::Action;
+use super::common::*;
+use super::current_turn::*;
 
+pub struct CompletedTurn {
+    pub player: Player,
+    pub actions: Vec<Action>,
+    pub ended_with: CurrentTurnStatus,
+}